@@ -0,0 +1,137 @@
+use std::time::{Duration, Instant};
+
+/// Time between frames while something is animating.
+pub const FRAME_DELAY: Duration = Duration::from_millis(30);
+
+/// How long cleared lines flash before fading.
+const FLASH: Duration = Duration::from_millis(240);
+/// How long each flash shows for.
+const FLASH_STEP: Duration = Duration::from_millis(60);
+/// How long each step of the fade lasts.
+const FADE_STEP: Duration = Duration::from_millis(70);
+
+/// How long a score message floats above the board.
+const FLOAT: Duration = Duration::from_millis(1200);
+/// How long a score message takes to rise by one row.
+const FLOAT_STEP: Duration = Duration::from_millis(300);
+
+/// Shrinking glyphs shown as cleared squares fade away, each one cell wide.
+pub const FADE_GLYPHS: [&str; 3] = ["◉", "•", "·"];
+
+/// What a clearing square looks like at some point of the animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClearFrame {
+    /// Alternating between the blast color and its usual color.
+    Flash {
+        lit: bool,
+    },
+    /// Drawn with the glyph at this index of `FADE_GLYPHS`.
+    Fade(usize),
+    Done,
+}
+
+/// The squares of lines that were just cleared, flashing and then fading away.
+#[derive(Debug, Clone)]
+pub struct ClearAnimation {
+    cells: Vec<usize>,
+    started: Instant,
+}
+
+impl ClearAnimation {
+    /// Start animating `cells`, indexes into the canvas contents.
+    pub fn new(cells: Vec<usize>) -> Self {
+        ClearAnimation {
+            cells,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn cells(&self) -> &[usize] {
+        &self.cells
+    }
+
+    pub fn frame(&self) -> ClearFrame {
+        frame_at(self.started.elapsed())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame() == ClearFrame::Done
+    }
+}
+
+/// A message about the points just scored, drifting up from where they were scored.
+#[derive(Debug, Clone)]
+pub struct FloatingText {
+    text: String,
+    anchor: usize,
+    started: Instant,
+}
+
+impl FloatingText {
+    /// Show `text` starting at `anchor`, an index into the canvas contents.
+    pub fn new(text: String, anchor: usize) -> Self {
+        FloatingText {
+            text,
+            anchor,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn anchor(&self) -> usize {
+        self.anchor
+    }
+
+    /// How many rows above the anchor the text has risen.
+    pub fn rise(&self) -> u16 {
+        rise_at(self.started.elapsed())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.started.elapsed() >= FLOAT
+    }
+}
+
+fn rise_at(elapsed: Duration) -> u16 {
+    (elapsed.min(FLOAT).as_millis() / FLOAT_STEP.as_millis()) as u16
+}
+
+fn frame_at(elapsed: Duration) -> ClearFrame {
+    if elapsed < FLASH {
+        let step = elapsed.as_millis() / FLASH_STEP.as_millis();
+        return ClearFrame::Flash {
+            lit: step.is_multiple_of(2),
+        };
+    }
+
+    match ((elapsed - FLASH).as_millis() / FADE_STEP.as_millis()) as usize {
+        step if step < FADE_GLYPHS.len() => ClearFrame::Fade(step),
+        _ => ClearFrame::Done,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn flashes_then_fades() {
+        let ms = Duration::from_millis;
+        assert_eq!(frame_at(ms(0)), ClearFrame::Flash { lit: true });
+        assert_eq!(frame_at(ms(70)), ClearFrame::Flash { lit: false });
+        assert_eq!(frame_at(ms(240)), ClearFrame::Fade(0));
+        assert_eq!(frame_at(ms(400)), ClearFrame::Fade(2));
+        assert_eq!(frame_at(ms(450)), ClearFrame::Done);
+    }
+
+    #[test]
+    fn text_floats_up() {
+        let ms = Duration::from_millis;
+        assert_eq!(rise_at(ms(0)), 0);
+        assert_eq!(rise_at(ms(650)), 2);
+        assert_eq!(rise_at(ms(5000)), 4);
+    }
+}
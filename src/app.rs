@@ -1,53 +1,204 @@
-use anyhow::{Result, bail};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-use jcblocks::{
-    block::{self, Point},
-    canvas::PointStatus,
-    game::Game,
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::Utc;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
 };
+use jcblocks::{block::Point, canvas::PointStatus, game::Game};
 use ratatui::{
     DefaultTerminal, Frame,
     buffer::Buffer,
-    layout::{Constraint, Flex, Layout, Rect},
+    layout::{Constraint, Flex, Layout, Position, Rect},
     style::{Modifier, Style, Stylize},
     symbols::border,
-    text::{Line, Text},
-    widgets::{Block, BorderType, Clear, Paragraph, Widget},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Clear, Paragraph, Widget, Wrap},
 };
 
-use crate::scoreboard::{LocalScoreBoard, Scoreboard};
+use crate::animation::{self, ClearAnimation, ClearFrame, FloatingText};
+use crate::board::Board;
+use crate::bot;
+use crate::daily::{self, DailyChallenge, Standings};
+use crate::demo::{self, Demo};
+use crate::generator::BlockGenerator;
+use crate::history::{History, Snapshot};
+use crate::keymap::{Action, KeyMatch, Keymap};
+use crate::layout::{Contents, ScreenLayout};
+use crate::mode::GameMode;
+use crate::paths;
+use crate::player;
+use crate::puzzle::{Puzzle, PuzzleBook, Status};
+use crate::replay::{Playback, Replay};
+use crate::save::{LocalSaveSlot, SavedGame};
+use crate::scoreboard::{HighScore, LocalScoreBoard, Scoreboard};
+use crate::scoring::Scorer;
+use crate::session::{Move, Session, extent};
+use crate::theme::Theme;
 
 use super::block_index::*;
-use super::config::*;
+use super::config::{Config, CursorPolicy, HAND_BLOCK_REPRESENTATION};
 
 #[derive(Debug)]
 pub struct App {
     exit: bool,
-    game_over: bool,
-    game: Game,
-    blocks: Vec<block::Block>,
+    session: Session,
     selected: BlockIndex,
+    /// The player's score, with combo bonuses on top of the game's own points.
+    scorer: Scorer,
+    mode: GameMode,
+    /// The size of the board new games are dealt on, and how many obstacles they have.
+    board: Board,
+    /// Placements made this game.
+    moves: usize,
+    /// When the first block of this game was placed, starting the blitz clock.
+    started: Option<Instant>,
     cursor_position: Point,
     center: Point,
     board_width: i32,
     board_height: i32,
     show_conflict_popup: bool,
+    show_resume_prompt: bool,
+    history: History,
+    assisted: bool,
+    /// Undo can be used in this game, set when it starts and kept when it's resumed.
+    allow_undo: bool,
     scoreboard: LocalScoreBoard,
+    assisted_scoreboard: LocalScoreBoard,
+    save_slot: LocalSaveSlot,
+    config: Config,
+    keymap: Keymap,
+    player_name: Option<String>,
+    name_entry: Option<String>,
+    theme: Theme,
+    daily: Option<DailyChallenge>,
+    /// Today's scoreboard and the player's streak, kept to hand as the screen is redrawn often.
+    daily_standings: Standings,
+    practice: bool,
+    replay: Replay,
+    playback: Option<Playback>,
+    hint: Option<Move>,
+    hints_used: u32,
+    demo: Option<Demo>,
+    /// The player's game, to go back to when the demo is interrupted.
+    before_demo: Option<Snapshot>,
+    /// A block picked up from the hand with the mouse, to be dropped on the board.
+    dragging: bool,
+    /// The start of a key sequence bound to an action.
+    pending_keys: Vec<KeyEvent>,
+    /// Lines just cleared by the player, still disappearing.
+    clearing: Option<ClearAnimation>,
+    /// The points the last placement earned, floating above the board.
+    award: Option<FloatingText>,
+    /// The puzzle being attempted, in place of a dealt game.
+    puzzle: Option<Puzzle>,
+    puzzle_book: Option<PuzzleBook>,
 }
 
 impl App {
-    pub fn new() -> Result<Self> {
-        let game = Game::default();
+    /// A new game as configured, or the saved one if there is one for the same mode and board.
+    pub fn new(config: &Config, db_path: &Path) -> Result<Self> {
+        // the player always has one selected block and zero or more additional blocks.
+        let blocks_per_turn = config.game.blocks_per_turn;
+        let daily = match config.game.daily {
+            true => Some(DailyChallenge::new(db_path, daily::today())?),
+            false => None,
+        };
+        // the daily challenge is the same game for everyone, so it is always endless.
+        let (mode, board) = match daily {
+            Some(_) => (GameMode::Endless, Board::default()),
+            None => (config.game.mode, config.board()),
+        };
+        let session = Session::on_board(
+            &board,
+            new_generator(seed(config, daily.as_ref())),
+            blocks_per_turn,
+        );
+        let mut app = App::start(config, db_path, session, mode, board, daily)?;
+
+        // pick up where the player left off, as long as the save still makes sense and was for
+        // the same mode and board. The daily challenge always starts afresh. Obstacles are
+        // compared with where the board would scatter them, which may be fewer than asked for.
+        if app.daily.is_none()
+            && let Some(saved) = app.save_slot.load().ok().flatten()
+            && saved.mode == mode
+            && saved.rows == board.rows
+            && saved.columns == board.columns
+            && saved.obstacles == saved.seed.map_or_else(Vec::new, |seed| board.scatter(seed))
+            && let Ok((session, selected)) = saved.restore(blocks_per_turn)
+        {
+            app.session = session;
+            app.selected = selected;
+            app.scorer = saved.scorer();
+            app.moves = saved.moves;
+            app.assisted = saved.assisted;
+            app.allow_undo = saved.allow_undo;
+            app.hints_used = saved.hints;
+            app.show_resume_prompt = true;
+            app.replay = Replay::new(app.session.game(), app.session.hand(), app.session.seed())
+                .with_obstacles(app.session.obstacles());
+            app.select_playable();
+            app.follow_selection();
+        }
+
+        Ok(app)
+    }
+
+    /// Watch a recorded game instead of playing one.
+    pub fn replay(config: &Config, db_path: &Path, replay: &Replay) -> Result<Self> {
+        let frames = replay.frames()?;
+        let hand_size = frames
+            .iter()
+            .map(|f| f.blocks.len())
+            .max()
+            .unwrap_or(config.game.blocks_per_turn);
+        let board = Board {
+            rows: replay.rows,
+            columns: replay.columns,
+            obstacles: replay.obstacles.len(),
+        };
+        let session = Session::on_board(&board, BlockGenerator::new(replay.seed), hand_size)
+            .with_obstacles(replay.obstacles.clone());
+
+        let mut app = App::start(config, db_path, session, GameMode::Endless, board, None)?;
+        app.playback = Some(Playback::new(frames));
+        app.show_frame();
+
+        Ok(app)
+    }
+
+    /// Attempt a puzzle instead of a dealt game. Progress is recorded in the puzzle book rather
+    /// than on the scoreboards, and puzzles are never saved.
+    pub fn puzzle(config: &Config, db_path: &Path, puzzle: Puzzle) -> Result<Self> {
+        let board = Board {
+            rows: puzzle.rows,
+            columns: puzzle.columns,
+            obstacles: 0,
+        };
+        let session = puzzle.session();
+
+        let mut app = App::start(config, db_path, session, GameMode::Endless, board, None)?;
+        app.puzzle_book = Some(PuzzleBook::new(db_path)?);
+        app.puzzle = Some(puzzle);
+
+        Ok(app)
+    }
 
+    /// A fresh game of `mode` on `board`, starting from `session`. Every way of starting a game
+    /// goes through here, so none of them has to undo another's setup.
+    fn start(
+        config: &Config,
+        db_path: &Path,
+        session: Session,
+        mode: GameMode,
+        board: Board,
+        daily: Option<DailyChallenge>,
+    ) -> Result<Self> {
         // block coordinates include negative numbers, so having these as i32 just reduces the
         // number of casts we have to do later.
-        let board_height = game.canvas.rows as i32;
-        let board_width = game.canvas.columns as i32;
-
-        // the player always has one selected block and zero or more additional blocks.
-        let blocks = game
-            .generate_blocks(NUM_BLOCKS_PER_TURN)
-            .expect("Should be able to generate blocks for an empty canvas.");
+        let board_height = board.rows as i32;
+        let board_width = board.columns as i32;
 
         // noting the center position is useful as it gives a place to initially place blocks where
         // they are ~guaranteed to fit without wrap
@@ -56,39 +207,399 @@ impl App {
             y: board_height / 2 - 1,
         };
 
-        let exe_path = std::env::current_exe()?;
-        let exe_dir = match exe_path.parent() {
-            Some(dir) => dir,
-            None => bail!("Cannot determine executable directory"),
+        let scoreboard =
+            LocalScoreBoard::for_board(config.scoreboard.size, db_path, mode, &board, false)?;
+        let assisted_scoreboard =
+            LocalScoreBoard::for_board(config.scoreboard.size, db_path, mode, &board, true)?;
+        let save_slot = LocalSaveSlot::new(db_path)?;
+        let player_name = player::resolve_name(config.player.name.as_deref());
+
+        // anyone who has had their official attempt today can still practice.
+        let practice = match (&daily, &player_name) {
+            (Some(daily), Some(name)) => daily.attempt(name)?.is_some(),
+            _ => false,
         };
 
-        // Create database path relative to executable
-        let db_path = exe_dir.join("app.db");
-        let scoreboard = LocalScoreBoard::new(5, db_path)?;
+        let selected = BlockIndex::new(session.hand().len());
+        let replay = Replay::new(session.game(), session.hand(), session.seed())
+            .with_obstacles(session.obstacles());
 
-        Ok(Self {
+        let mut app = Self {
             exit: false,
-            game_over: false,
-            game,
-            blocks,
-            selected: BlockIndex::default(),
+            session,
+            selected,
+            scorer: Scorer::default(),
+            mode,
+            board,
+            moves: 0,
+            started: None,
             cursor_position: center.clone(),
             center,
             board_width,
             board_height,
             show_conflict_popup: false,
+            show_resume_prompt: false,
+            history: History::new(config.game.undo_history),
+            assisted: false,
+            allow_undo: config.game.allow_undo,
             scoreboard,
-        })
+            assisted_scoreboard,
+            save_slot,
+            config: config.clone(),
+            keymap: config.keymap()?,
+            player_name,
+            name_entry: None,
+            theme: config.theme()?,
+            daily,
+            daily_standings: Standings::default(),
+            practice,
+            replay,
+            playback: None,
+            hint: None,
+            hints_used: 0,
+            demo: None,
+            before_demo: None,
+            dragging: false,
+            pending_keys: Vec::new(),
+            clearing: None,
+            award: None,
+            puzzle: None,
+            puzzle_book: None,
+        };
+        app.select_playable();
+        app.follow_selection();
+        app.refresh_daily_standings()?;
+
+        Ok(app)
     }
 
     fn reset(&mut self) {
-        self.game.reset();
-        self.game_over = false;
-        self.blocks = self
-            .game
-            .generate_blocks(NUM_BLOCKS_PER_TURN)
-            .expect("Should be able to generate blocks for an empty canvas.");
-        self.selected = BlockIndex::default();
+        let blocks_per_turn = self.session.blocks_per_turn();
+        self.session = match &self.puzzle {
+            Some(puzzle) => puzzle.session(),
+            None => Session::on_board(
+                &self.board,
+                new_generator(seed(&self.config, self.daily.as_ref())),
+                blocks_per_turn,
+            ),
+        };
+        self.selected = BlockIndex::new(self.session.hand().len());
+        self.scorer = Scorer::default();
+        self.moves = 0;
+        self.started = None;
+        self.cursor_position = self.clamped(&self.center);
+        self.history.clear();
+        self.clearing = None;
+        self.award = None;
+        self.assisted = false;
+        self.allow_undo = self.config.game.allow_undo;
+        self.hint = None;
+        self.hints_used = 0;
+        self.replay = Replay::new(
+            self.session.game(),
+            self.session.hand(),
+            self.session.seed(),
+        )
+        .with_obstacles(self.session.obstacles());
+        self.select_playable();
+        self.follow_selection();
+    }
+
+    /// Move the selection on to the next block that can still be placed, unless the selected one
+    /// can be.
+    fn select_playable(&mut self) {
+        self.skip_unplayable(BlockIndex::next);
+    }
+
+    /// Keep stepping the selection until it lands on a block that can be placed.
+    fn skip_unplayable(&mut self, step: fn(&mut BlockIndex)) {
+        for _ in 0..self.session.hand().len() {
+            if self.session.is_playable(self.selected.current()) {
+                return;
+            }
+            step(&mut self.selected);
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            session: self.session.clone(),
+            selected: self.selected.clone(),
+            scorer: self.scorer.clone(),
+            moves: self.moves,
+        }
+    }
+
+    fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        self.session = snapshot.session;
+        self.selected = snapshot.selected;
+        self.scorer = snapshot.scorer;
+        self.moves = snapshot.moves;
+        self.hint = None;
+        self.clearing = None;
+        self.award = None;
+        self.select_playable();
+        self.follow_selection();
+    }
+
+    /// The score to show: the player's, with bonuses, or the demo's plain points.
+    fn score(&self) -> usize {
+        match self.demo {
+            Some(_) => self.session.score(),
+            None => self.scorer.total(),
+        }
+    }
+
+    /// Time left on the clock of a blitz game that is still going. The clock starts with the
+    /// first placement.
+    fn time_left(&self) -> Option<Duration> {
+        if self.mode != GameMode::Blitz || self.demo.is_some() || self.is_game_over() {
+            return None;
+        }
+
+        let limit = Duration::from_secs(self.config.game.blitz_minutes * 60);
+        let elapsed = self.started.map(|started| started.elapsed());
+        Some(limit.saturating_sub(elapsed.unwrap_or_default()))
+    }
+
+    /// Placements left in a moves game, or a puzzle with a limit.
+    fn moves_left(&self) -> Option<usize> {
+        if let Some(puzzle) = &self.puzzle {
+            return puzzle.moves.map(|limit| limit.saturating_sub(self.moves));
+        }
+        match self.mode {
+            GameMode::Moves => Some(self.config.game.moves.saturating_sub(self.moves)),
+            _ => None,
+        }
+    }
+
+    /// The game has ended, which it never does while watching a replay or the demo.
+    fn is_game_over(&self) -> bool {
+        self.playback.is_none() && self.demo.is_none() && self.session.is_game_over()
+    }
+
+    /// Let the computer play on the board until a key is pressed, putting the player's game aside
+    /// until then.
+    ///
+    /// The demo is dealt from the configured seed, if any, so it always plays the same games.
+    pub fn start_demo(&mut self) {
+        if self.demo.is_some() || self.playback.is_some() || self.puzzle.is_some() {
+            return;
+        }
+
+        self.before_demo = Some(self.snapshot());
+        self.clearing = None;
+        self.award = None;
+        self.session = Session::on_board(
+            &self.board,
+            new_generator(self.config.game.seed),
+            self.session.blocks_per_turn(),
+        );
+        self.hint = None;
+        self.demo = Some(Demo::new());
+        self.show_demo();
+    }
+
+    /// Give the board back to the player, just as they left it.
+    fn stop_demo(&mut self) {
+        self.demo = None;
+        if let Some(snapshot) = self.before_demo.take() {
+            self.apply_snapshot(snapshot);
+        }
+    }
+
+    /// Point the cursor at the move the demo is about to make.
+    fn show_demo(&mut self) {
+        let Some(demo) = &self.demo else {
+            return;
+        };
+
+        let (selected, cursor) = match demo.aiming() {
+            Some(m) => (
+                m.selected,
+                Point {
+                    x: m.column,
+                    y: m.row,
+                },
+            ),
+            None => (0, self.center.clone()),
+        };
+        self.selected = BlockIndex::restore(selected, self.session.hand().len());
+        self.cursor_position = self.clamped(&cursor);
+    }
+
+    fn current_scoreboard(&mut self) -> &mut LocalScoreBoard {
+        if self.assisted {
+            &mut self.assisted_scoreboard
+        } else {
+            &mut self.scoreboard
+        }
+    }
+
+    /// Is this game the player's one official attempt at today's daily challenge?
+    fn is_official_attempt(&self) -> bool {
+        self.daily.is_some() && !self.practice
+    }
+
+    /// End the game, keeping its replay and recording the score.
+    fn finish_game(&mut self) -> Result<()> {
+        self.session.resign();
+        if !self.replay.is_empty() {
+            self.replay.save(&paths::replay_dir()?)?;
+        }
+        self.record_score()
+    }
+
+    /// End the puzzle once it is solved or can't be any more, noting the attempt in the puzzle
+    /// book.
+    fn check_puzzle(&mut self) -> Result<()> {
+        let Some(puzzle) = &self.puzzle else {
+            return Ok(());
+        };
+
+        let status = puzzle.status(&self.session, &self.scorer, self.moves);
+        if status == Status::Playing {
+            return Ok(());
+        }
+        self.session.resign();
+        if let Some(book) = &self.puzzle_book {
+            book.record(&puzzle.id, (status == Status::Solved).then_some(self.moves))?;
+        }
+
+        Ok(())
+    }
+
+    /// The puzzle was solved, rather than given up on or failed.
+    fn is_solved(&self) -> bool {
+        self.puzzle.as_ref().is_some_and(|puzzle| {
+            puzzle.status(&self.session, &self.scorer, self.moves) == Status::Solved
+        })
+    }
+
+    /// Show the replay's current frame on the board.
+    fn show_frame(&mut self) {
+        let Some(playback) = &self.playback else {
+            return;
+        };
+
+        let frame = playback.frame();
+        let hand_size = self.session.blocks_per_turn();
+        self.session = Session::resume(
+            Game {
+                canvas: frame.canvas.clone(),
+                score: frame.score,
+            },
+            frame.blocks.clone(),
+            self.session.generator().clone(),
+            hand_size,
+        )
+        .with_obstacles(self.session.obstacles().to_vec());
+        self.selected = BlockIndex::restore(frame.selected, frame.blocks.len());
+        self.scorer = frame.scorer.clone();
+        self.cursor_position = match &frame.cursor {
+            Some(cursor) => cursor.clone(),
+            None => self.clamped(&self.center),
+        };
+    }
+
+    /// Record the final score, asking for the player's name first if we don't know it and the
+    /// score is good enough to be remembered.
+    ///
+    /// The official daily attempt always goes on the daily scoreboard. Later practice games
+    /// aren't recorded anywhere, as they are dealt the blocks of a game already played.
+    fn record_score(&mut self) -> Result<()> {
+        let score = self.scorer.total() as i64;
+        if let Some(daily) = &self.daily {
+            if self.practice {
+                return Ok(());
+            }
+            match &self.player_name {
+                Some(name) => {
+                    daily.record(name, score)?;
+                    self.practice = true;
+                    self.refresh_daily_standings()?;
+                }
+                None => self.name_entry = Some(String::new()),
+            }
+            return Ok(());
+        }
+
+        match self.player_name.clone() {
+            Some(name) => {
+                let high_score = HighScore::new(&name, score, Utc::now())
+                    .with_seed(self.session.seed())
+                    .with_hints(self.hints_used)
+                    .with_breakdown(self.scorer.breakdown().clone());
+                self.current_scoreboard().add_high_score(high_score)?;
+            }
+            None if self.current_scoreboard().would_rank(score) => {
+                self.name_entry = Some(String::new());
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// Read today's scoreboard and the player's streak again, so drawing doesn't have to.
+    fn refresh_daily_standings(&mut self) -> Result<()> {
+        if let Some(daily) = &self.daily {
+            self.daily_standings =
+                daily.standings(self.config.scoreboard.shown, self.player_name.as_deref())?;
+        }
+
+        Ok(())
+    }
+
+    /// Persist the game in progress so it can be resumed next time, or forget it if it is over.
+    ///
+    /// Daily challenges aren't saved. Quitting part way through the official attempt counts as
+    /// finishing it, otherwise the day's blocks could be scouted and the attempt started over.
+    fn save_progress(&mut self) -> Result<()> {
+        // puzzles are short enough to start again.
+        if self.puzzle.is_some() {
+            return Ok(());
+        }
+        if self.daily.is_some() {
+            let started = self.session.score() > 0
+                || self
+                    .session
+                    .canvas()
+                    .contents()
+                    .iter()
+                    .any(|p| matches!(p, PointStatus::Occupied));
+            if self.is_official_attempt() && started && !self.session.is_game_over() {
+                self.finish_game()?;
+            }
+            return Ok(());
+        }
+        // the clock can't be stopped, so a blitz game is over once it's left, and a zen game only
+        // ends when the player leaves it. Either way the score counts.
+        if matches!(self.mode, GameMode::Blitz | GameMode::Zen) {
+            if self.moves > 0 && !self.is_game_over() {
+                self.finish_game()?;
+            }
+            if self.mode == GameMode::Zen {
+                return self.save_slot.clear();
+            }
+            return Ok(());
+        }
+        if self.session.is_game_over() {
+            return self.save_slot.clear();
+        }
+
+        self.save_slot.store(
+            &SavedGame::capture(
+                &self.session,
+                &self.selected,
+                &self.scorer,
+                self.mode,
+                self.moves,
+                self.assisted,
+                self.hints_used,
+            )
+            .with_allow_undo(self.allow_undo),
+        )
     }
 
     /// Run the application's main loop.
@@ -106,174 +617,579 @@ impl App {
     }
 
     fn handle_events(&mut self) -> Result<()> {
+        if let Some(timeout) = self.timeout()
+            && !event::poll(timeout)?
+        {
+            return self.tick();
+        }
+
         match event::read()? {
             // it's important to check that the event is a key press event as
             // crossterm also emits key release and repeat events on Windows.
             Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                 self.handle_key_event(key_event)?
             }
+            Event::Mouse(mouse_event) => self.handle_mouse_event(mouse_event)?,
             _ => {}
         };
         Ok(())
     }
 
+    /// How long to wait for a key before moving things along without one: the next frame of an
+    /// animation, the next move of a replay or the demo, the blitz clock, or starting the demo
+    /// once the player has been idle for long enough.
+    fn timeout(&self) -> Option<Duration> {
+        if self.clearing.is_some() || self.award.is_some() {
+            return Some(animation::FRAME_DELAY);
+        }
+        if let Some(playback) = &self.playback {
+            return playback.next_delay();
+        }
+        if self.demo.is_some() {
+            return Some(demo::STEP_DELAY);
+        }
+        // the countdown changes as each second ticks over.
+        if let Some(left) = self.time_left()
+            && self.started.is_some()
+        {
+            return Some(Duration::from_nanos(left.subsec_nanos().max(1) as u64));
+        }
+
+        match self.config.game.demo_after {
+            0 => None,
+            // don't walk away from someone typing their name.
+            _ if self.name_entry.is_some() => None,
+            seconds => Some(Duration::from_secs(seconds)),
+        }
+    }
+
+    /// No key was pressed before the `timeout`.
+    fn tick(&mut self) -> Result<()> {
+        if self.time_left().is_some_and(|left| left.is_zero()) {
+            return self.finish_game();
+        }
+
+        // anything else waits for the animations to finish.
+        if self.clearing.is_some() || self.award.is_some() {
+            if self
+                .clearing
+                .as_ref()
+                .is_some_and(ClearAnimation::is_finished)
+            {
+                self.clearing = None;
+            }
+            if self.award.as_ref().is_some_and(FloatingText::is_finished) {
+                self.award = None;
+            }
+        } else if let Some(playback) = self.playback.as_mut() {
+            playback.advance();
+            self.show_frame();
+        } else if let Some(demo) = self.demo.as_mut() {
+            let (seed, blocks_per_turn) = (self.config.game.seed, self.session.blocks_per_turn());
+            let board = self.board;
+            demo.step(&mut self.session, || {
+                Session::on_board(&board, new_generator(seed), blocks_per_turn)
+            });
+            self.show_demo();
+        } else if self.time_left().is_none() {
+            // the demo would only eat into the blitz clock.
+            self.start_demo();
+        }
+
+        Ok(())
+    }
+
     fn handle_key_event(&mut self, key_event: KeyEvent) -> Result<()> {
-        // moving a block could result in part of it escaping the playing board, this helper is for
-        // checking that condition
-        let is_selected_block_within_boundary = |cursor: &Point| {
-            for p in self.blocks[self.selected.current()].coordinates() {
-                if p.x + cursor.x >= self.board_width || p.x + cursor.x < 0 {
-                    return false;
+        self.show_conflict_popup = false;
+
+        // any key takes the board back from the demo.
+        if self.demo.is_some() {
+            self.stop_demo();
+            return Ok(());
+        }
+
+        // the player is typing their name for the scoreboard.
+        if let Some(name) = self.name_entry.as_mut() {
+            match key_event.code {
+                KeyCode::Char(c) if name.chars().count() < player::MAX_NAME_LENGTH => name.push(c),
+                KeyCode::Backspace => {
+                    name.pop();
                 }
-                if p.y + cursor.y >= self.board_height || p.y + cursor.y < 0 {
-                    return false;
+                KeyCode::Enter => {
+                    let name = player::sanitize(name);
+                    if !name.is_empty() {
+                        self.name_entry = None;
+                        self.player_name = Some(name);
+                        self.record_score()?;
+                    }
+                }
+                // skip recording the score
+                KeyCode::Esc => self.name_entry = None,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // a saved game was restored, let the player decide whether to keep it.
+        if self.show_resume_prompt {
+            match self.keymap.action(&key_event) {
+                Some(Action::Continue) => self.show_resume_prompt = false,
+                Some(Action::NewGame) => {
+                    self.show_resume_prompt = false;
+                    self.save_slot.clear()?;
+                    self.reset();
                 }
+                Some(Action::Quit) => self.exit(),
+                _ => {}
             }
+            return Ok(());
+        }
+
+        if self.playback.is_some() {
+            self.handle_playback_key_event(key_event);
+            return Ok(());
+        }
 
-            true
+        // some actions take more than one key, hold on to the keys until they add up to one.
+        self.pending_keys.push(key_event);
+        let action = match self.keymap.resolve(&self.pending_keys) {
+            KeyMatch::Action(action) => action,
+            KeyMatch::Pending => return Ok(()),
+            KeyMatch::Unbound => {
+                self.pending_keys.clear();
+                // number keys pick a block from the hand, counting from 1.
+                if let KeyCode::Char(c) = key_event.code
+                    && let Some(n) = c.to_digit(10)
+                    && n > 0
+                    && !self.is_game_over()
+                {
+                    self.select(n as usize - 1);
+                }
+                return Ok(());
+            }
         };
+        self.pending_keys.clear();
 
-        self.show_conflict_popup = false;
-        match key_event.code {
+        match action {
             // quit
-            KeyCode::Char('q') => Ok(self.exit()),
+            Action::Quit => {
+                self.save_progress()?;
+                // quitting the daily challenge may need a name for the scoreboard first.
+                if self.name_entry.is_none() {
+                    self.exit();
+                }
+                Ok(())
+            }
 
             // place block
-            KeyCode::Char(' ') => {
-                let Point { y: row, x: column } = self.cursor_position;
-
-                // attempt to place the block
-                if self
-                    .game
-                    .maybe_place_block(&self.blocks[self.selected.current()], row, column)
-                    .is_ok()
-                {
-                    // yes, remove is highly inefficient, but this vector is always very tiny,
-                    // so bite me.
-                    self.blocks.remove(self.selected.place());
-                    if self.blocks.len() < 1 {
-                        match self.game.generate_blocks(NUM_BLOCKS_PER_TURN) {
-                            Some(blocks) => self.blocks = blocks,
-                            None => unreachable!("There is always a combination that will work."),
-                        }
-                    }
+            Action::Place => {
+                if self.is_game_over() {
+                    return Ok(());
+                }
 
-                    // check if the game can make progress.
-                    let mut can_fit_at_least_one = false;
-                    for block in self.blocks.iter() {
-                        if self.game.canvas.can_fit(&block).is_some() {
-                            can_fit_at_least_one = true;
-                            break;
-                        }
-                    }
-                    self.game_over = !can_fit_at_least_one;
-                    if self.game_over {
-                        self.scoreboard.add(env!("USER"), self.game.score as i64)?;
-                    }
-                    self.cursor_position = self.center.clone();
-                } else {
-                    self.show_conflict_popup = true;
+                self.place()
+            }
+
+            // cursor left
+            Action::MoveLeft => {
+                if self.is_game_over() {
+                    return Ok(());
                 }
 
+                self.move_cursor(-1, 0);
                 Ok(())
             }
 
-            // cursor left
-            KeyCode::Char('h') | KeyCode::Left => {
-                if self.game_over {
+            // cursor down
+            Action::MoveDown => {
+                if self.is_game_over() {
                     return Ok(());
                 }
 
-                let maybe_new_cursor_position = Point {
-                    x: self.cursor_position.x - 1,
-                    y: self.cursor_position.y,
-                };
-                if is_selected_block_within_boundary(&maybe_new_cursor_position) {
-                    self.cursor_position = maybe_new_cursor_position;
+                self.move_cursor(0, -1);
+                Ok(())
+            }
+
+            // cursor up
+            Action::MoveUp => {
+                if self.is_game_over() {
+                    return Ok(());
                 }
 
+                self.move_cursor(0, 1);
                 Ok(())
             }
 
-            // cursor down
-            KeyCode::Char('j') | KeyCode::Down => {
-                if self.game_over {
+            // cursor right
+            Action::MoveRight => {
+                if self.is_game_over() {
+                    return Ok(());
+                }
+
+                self.move_cursor(1, 0);
+                Ok(())
+            }
+
+            // slide the block all the way to an edge
+            Action::JumpLeft | Action::JumpDown | Action::JumpUp | Action::JumpRight => {
+                if self.is_game_over() {
                     return Ok(());
                 }
 
-                let maybe_new_cursor_position = Point {
-                    x: self.cursor_position.x,
-                    y: self.cursor_position.y - 1,
+                let (dx, dy) = match action {
+                    Action::JumpLeft => (-self.board_width, 0),
+                    Action::JumpDown => (0, -self.board_height),
+                    Action::JumpUp => (0, self.board_height),
+                    _ => (self.board_width, 0),
                 };
-                if is_selected_block_within_boundary(&maybe_new_cursor_position) {
-                    self.cursor_position = maybe_new_cursor_position;
+                self.cursor_position = self.clamped(&Point {
+                    x: self.cursor_position.x + dx,
+                    y: self.cursor_position.y + dy,
+                });
+                Ok(())
+            }
+
+            // cycle block selection, skipping over blocks that don't fit anywhere.
+            Action::CycleNext => {
+                if self.is_game_over() {
+                    return Ok(());
                 }
 
+                self.selected.next();
+                self.skip_unplayable(BlockIndex::next);
+                self.follow_selection();
+
                 Ok(())
             }
 
-            // cursor up
-            KeyCode::Char('k') | KeyCode::Up => {
-                if self.game_over {
+            Action::CyclePrevious => {
+                if self.is_game_over() {
                     return Ok(());
                 }
 
-                let maybe_new_cursor_position = Point {
-                    x: self.cursor_position.x,
-                    y: self.cursor_position.y + 1,
-                };
-                if is_selected_block_within_boundary(&maybe_new_cursor_position) {
-                    self.cursor_position = maybe_new_cursor_position;
+                self.selected.previous();
+                self.skip_unplayable(BlockIndex::previous);
+                self.follow_selection();
+
+                Ok(())
+            }
+
+            // undo the last placement, this marks the game as assisted. The official daily attempt
+            // has no second chances, and neither does a game started without undo.
+            Action::Undo => {
+                if self.is_game_over() || self.is_official_attempt() || !self.allow_undo {
+                    return Ok(());
+                }
+
+                if let Some(previous) = self.history.undo(self.snapshot()) {
+                    self.apply_snapshot(previous);
+                    self.replay.undo();
+                    self.assisted = true;
                 }
 
                 Ok(())
             }
 
-            // cursor right
-            KeyCode::Char('l') | KeyCode::Right => {
-                if self.game_over {
+            // redo an undone placement
+            Action::Redo => {
+                if self.is_game_over() {
                     return Ok(());
                 }
 
-                let maybe_new_cursor_position = Point {
-                    x: self.cursor_position.x + 1,
-                    y: self.cursor_position.y,
-                };
-                if is_selected_block_within_boundary(&maybe_new_cursor_position) {
-                    self.cursor_position = maybe_new_cursor_position;
+                if let Some(next) = self.history.redo(self.snapshot()) {
+                    self.apply_snapshot(next);
+                    self.replay.redo();
                 }
 
                 Ok(())
             }
 
-            // cycle block selection
-            KeyCode::Char('n') => {
-                if self.game_over {
+            // suggest a move, which is noted with the score. The official daily attempt has to be
+            // played unaided.
+            Action::Hint => {
+                if self.is_game_over() || self.is_official_attempt() {
                     return Ok(());
                 }
 
-                self.selected.cycle();
-                self.cursor_position = self.center.clone();
+                self.hint = bot::plan(&self.session).and_then(|plan| plan.moves.first().copied());
+                if self.hint.is_some() {
+                    self.hints_used += 1;
+                }
 
                 Ok(())
             }
 
-            KeyCode::Enter => {
-                if self.game_over {
+            Action::NewGame => {
+                if self.is_game_over() {
                     self.reset();
                 }
                 Ok(())
             }
-            _ => Ok(()),
+
+            // only means something when asked whether to resume.
+            Action::Continue => Ok(()),
         }
     }
 
-    fn render_local_scoreboard(&self, area: Rect, buf: &mut Buffer) {
-        let content = self
-            .scoreboard
+    /// Place the selected block at the cursor, or warn the player that it doesn't fit.
+    fn place(&mut self) -> Result<()> {
+        // a key can arrive after the blitz clock runs out but before the tick that would notice.
+        if self.time_left().is_some_and(|left| left.is_zero()) {
+            return self.finish_game();
+        }
+
+        let Point { y: row, x: column } = self.cursor_position;
+        let m = Move {
+            selected: self.selected.current(),
+            row,
+            column,
+        };
+
+        // attempt to place the block
+        let before = self.snapshot();
+        if let Ok(placed) = self.session.apply(&m) {
+            self.history.record(before);
+            self.moves += 1;
+            self.started.get_or_insert_with(Instant::now);
+            self.hint = None;
+            self.replay.place(m.selected, row, column);
+            // the points float up from the middle of the cleared squares.
+            if let Some(award) = self
+                .scorer
+                .score(placed.lines_cleared, self.session.is_clear())
+                && let Some(&anchor) = placed.cleared.get(placed.cleared.len() / 2)
+            {
+                self.award = Some(FloatingText::new(award.to_string(), anchor));
+            }
+            if self.config.layout.animations && !placed.cleared.is_empty() {
+                self.clearing = Some(ClearAnimation::new(placed.cleared.clone()));
+            }
+            self.selected.placed(self.session.hand().len());
+            if placed.dealt {
+                self.replay.hand(self.session.hand());
+            }
+            // zen games never end while anything fits, a dead hand is swapped for a new one.
+            if self.mode == GameMode::Zen && self.session.is_game_over() && self.session.reshuffle()
+            {
+                self.selected = BlockIndex::new(self.session.hand().len());
+                self.replay.hand(self.session.hand());
+            }
+            self.select_playable();
+
+            if self.puzzle.is_some() {
+                self.check_puzzle()?;
+            } else if self.session.is_game_over() || self.moves_left() == Some(0) {
+                self.finish_game()?;
+            }
+            self.follow_selection();
+        } else {
+            self.show_conflict_popup = true;
+        }
+
+        Ok(())
+    }
+
+    /// Click a block in the hand to select it and click the board to place it, or drag it from
+    /// the hand and drop it on the board. The selected block follows the mouse over the board.
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> Result<()> {
+        // clicking takes the board back from the demo, like any key.
+        if self.demo.is_some() {
+            if let MouseEventKind::Down(_) = mouse_event.kind {
+                self.stop_demo();
+            }
+            return Ok(());
+        }
+        if self.playback.is_some()
+            || self.name_entry.is_some()
+            || self.show_resume_prompt
+            || self.is_game_over()
+        {
+            return Ok(());
+        }
+
+        // the layout depends on the size of the whole terminal, as it does when drawing.
+        let (width, height) = crossterm::terminal::size()?;
+        let Some(layout) = self.screen_layout(Rect::new(0, 0, width, height)) else {
+            return Ok(());
+        };
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        let point = layout
+            .board_cells(self.board_height as u16, self.board_width as u16)
+            .iter()
+            .position(|cell| cell.contains(position))
+            .map(|i| Point {
+                x: i as i32 % self.board_width,
+                y: i as i32 / self.board_width,
+            });
+        let slot = layout
+            .hand_slots(self.session.blocks_per_turn() as u16)
+            .iter()
+            .position(|slot| slot.contains(position));
+
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.show_conflict_popup = false;
+                if let Some(slot) = slot {
+                    self.dragging = self.select(slot);
+                } else if let Some(point) = point {
+                    self.aim_at(point);
+                    self.place()?;
+                }
+            }
+            MouseEventKind::Moved | MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some(point) = point {
+                    self.aim_at(point);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                if std::mem::take(&mut self.dragging)
+                    && let Some(point) = point
+                {
+                    self.aim_at(point);
+                    self.place()?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Select the block at `slot` in the hand, as long as it can be played.
+    fn select(&mut self, slot: usize) -> bool {
+        if !self.session.is_playable(slot) || !self.selected.select(slot) {
+            return false;
+        }
+
+        self.follow_selection();
+        true
+    }
+
+    /// Move the selected block so it is centered on `point`, or as near as it can be while
+    /// staying on the board.
+    fn aim_at(&mut self, point: Point) {
+        let Some(block) = self.session.hand().get(self.selected.current()) else {
+            return;
+        };
+
+        let (min, max) = extent(block);
+        self.cursor_position = self.clamped(&Point {
+            x: point.x - (min.x + max.x) / 2,
+            y: point.y - (min.y + max.y) / 2,
+        });
+    }
+
+    /// The nearest cursor position to `cursor` that keeps the whole selected block on the board.
+    fn clamped(&self, cursor: &Point) -> Point {
+        let Some(block) = self.session.hand().get(self.selected.current()) else {
+            return cursor.clone();
+        };
+
+        let (min, max) = extent(block);
+        Point {
+            x: cursor.x.min(self.board_width - 1 - max.x).max(-min.x),
+            y: cursor.y.min(self.board_height - 1 - max.y).max(-min.y),
+        }
+    }
+
+    /// Move the cursor by `dx` columns and `dy` rows, sliding the block along the edge of the
+    /// board rather than off it. With wrapping on, pushing against an edge comes back in on the
+    /// other side.
+    fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let moved = self.clamped(&Point {
+            x: self.cursor_position.x + dx,
+            y: self.cursor_position.y + dy,
+        });
+        if moved != self.cursor_position || !self.config.game.wrap_cursor {
+            self.cursor_position = moved;
+            return;
+        }
+
+        let wrap = |position: i32, delta: i32, size: i32| match delta.signum() {
+            1 => -size,
+            -1 => 2 * size,
+            _ => position,
+        };
+        self.cursor_position = self.clamped(&Point {
+            x: wrap(self.cursor_position.x, dx, self.board_width),
+            y: wrap(self.cursor_position.y, dy, self.board_height),
+        });
+    }
+
+    /// Move the cursor for a newly selected block, as the player's cursor policy says.
+    fn follow_selection(&mut self) {
+        self.cursor_position = match self.config.game.cursor {
+            CursorPolicy::Stay if self.clamped(&self.cursor_position) == self.cursor_position => {
+                return;
+            }
+            CursorPolicy::Stay | CursorPolicy::Center => self.clamped(&self.center),
+            CursorPolicy::Clamp => self.clamped(&self.cursor_position),
+            CursorPolicy::BestFit => match bot::best_move(&self.session, self.selected.current()) {
+                Some(m) => Point {
+                    x: m.column,
+                    y: m.row,
+                },
+                None => self.clamped(&self.center),
+            },
+        };
+    }
+
+    /// Replay controls: pause, step, change speed and seek.
+    fn handle_playback_key_event(&mut self, key_event: KeyEvent) {
+        let Some(playback) = self.playback.as_mut() else {
+            return;
+        };
+
+        match (key_event.code, self.keymap.action(&key_event)) {
+            (_, Some(Action::Quit)) => self.exit = true,
+            (_, Some(Action::Place)) => playback.toggle_pause(),
+            (_, Some(Action::MoveLeft)) => playback.step_back(),
+            (_, Some(Action::MoveRight)) => playback.step_forward(),
+            (_, Some(Action::MoveUp)) => playback.faster(),
+            (_, Some(Action::MoveDown)) => playback.slower(),
+            (KeyCode::Home, _) => playback.seek(0),
+            (KeyCode::End, _) => playback.seek(playback.len()),
+            // jump to 0%, 10%, ... 90% of the way through
+            (KeyCode::Char(c), _) if c.is_ascii_digit() => {
+                let tenths = c.to_digit(10).unwrap_or_default() as usize;
+                playback.seek(playback.len() * tenths / 10);
+            }
+            _ => {}
+        }
+        self.show_frame();
+    }
+
+    fn render_playback_status(&self, playback: &Playback, area: Rect, buf: &mut Buffer) {
+        let state = if playback.is_playing() {
+            format!("Playing at {}x", playback.speed())
+        } else if playback.is_finished() {
+            "End of replay".to_string()
+        } else {
+            "Paused".to_string()
+        };
+
+        Paragraph::new(Text::from(format!(
+            "{} - move {} of {}",
+            state,
+            playback.position() + 1,
+            playback.len()
+        )))
+        .style(self.theme.scoreboard)
+        .centered()
+        .render(area, buf);
+    }
+
+    fn render_local_scoreboard(
+        &self,
+        title: &str,
+        scoreboard: &LocalScoreBoard,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        let content = scoreboard
             .all()
             .iter()
-            .take(3)
+            .take(self.config.scoreboard.shown)
             .map(|high_score| {
                 format!(
                     "{:<6} {:7}",
@@ -284,8 +1200,45 @@ impl App {
             .collect::<Vec<String>>()
             .join("\n");
 
-        Paragraph::new(Text::from(format!("Personal Best:\n{}", content)))
-            .yellow()
+        Paragraph::new(Text::from(format!("{}:\n{}", title, content)))
+            .style(self.theme.scoreboard)
+            .centered()
+            .render(area, buf);
+    }
+
+    fn render_daily_scoreboard(&self, area: Rect, buf: &mut Buffer) {
+        let content = self
+            .daily_standings
+            .leaderboard
+            .iter()
+            .map(|high_score| format!("{:<10} {:7}", high_score.name, high_score.score))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        Paragraph::new(Text::from(format!("Today's Best:\n{}", content)))
+            .style(self.theme.scoreboard)
+            .centered()
+            .render(area, buf);
+    }
+
+    fn render_daily_status(&self, area: Rect, buf: &mut Buffer) {
+        if self.player_name.is_none() {
+            return;
+        }
+
+        let streak = self.daily_standings.streak;
+        let mut lines = vec![Line::from(format!(
+            "Streak: {} day{}",
+            streak,
+            if streak == 1 { "" } else { "s" }
+        ))];
+        if let Some(attempt) = &self.daily_standings.attempt {
+            lines.push(Line::from(format!("Official: {}", attempt.score)));
+            lines.push(Line::from("Practicing"));
+        }
+
+        Paragraph::new(Text::from(lines))
+            .style(self.theme.scoreboard)
             .centered()
             .render(area, buf);
     }
@@ -296,7 +1249,7 @@ impl App {
             .scoreboard
             .all()
             .iter()
-            .take(3)
+            .take(self.config.scoreboard.shown)
             .map(|high_score| {
                 format!(
                     "{:<6} {:7} {:>10}",
@@ -309,44 +1262,57 @@ impl App {
             .join("\n");
 
         Paragraph::new(Text::from(format!("World Best:\n{}", content)))
-            .yellow()
+            .style(self.theme.scoreboard)
             .centered()
             .render(area, buf);
     }
 
-    fn render_game_board(&self, area: Rect, buf: &mut Buffer) {
-        // Gameboard layout
-        let [game_container] =
-            Layout::horizontal([Constraint::Length((self.board_width * 2) as u16)])
-                .flex(ratatui::layout::Flex::Center)
-                .areas(area);
+    /// What has to fit on the screen: the board, the hand and the scoreboards, if they're shown.
+    fn screen_contents(&self) -> Contents {
+        let scoreboard = match self.config.layout.show_scoreboard && self.puzzle.is_none() {
+            true => self.config.scoreboard.shown as u16 + 1,
+            false => 0,
+        };
+        Contents {
+            rows: self.board_height as u16,
+            columns: self.board_width as u16,
+            hand: self.session.blocks_per_turn() as u16,
+            scoreboard,
+            margin: self.config.layout.vertical_margin,
+        }
+    }
 
-        let game_rows = Layout::vertical(vec![Constraint::default(); self.board_height as usize])
-            .flex(ratatui::layout::Flex::Center)
-            .split(game_container);
+    /// Where everything goes on a screen the size of `area`, or `None` if it doesn't fit.
+    fn screen_layout(&self, area: Rect) -> Option<ScreenLayout> {
+        ScreenLayout::new(area, &self.screen_contents())
+    }
 
+    fn render_game_board(&self, layout: &ScreenLayout, buf: &mut Buffer) {
         // Get the current state of each coordinate within the playing area.
+        let obstacles = self.session.obstacles();
         let mut display_coords: Vec<DisplayPointStatus> = self
-            .game
-            .canvas
+            .session
+            .canvas()
             .contents()
             .iter()
-            .map(|p| {
-                if let PointStatus::Occupied = p {
-                    return DisplayPointStatus::Occupied;
-                }
-                DisplayPointStatus::Unoccupied
+            .enumerate()
+            .map(|(i, p)| match p {
+                PointStatus::Occupied if obstacles.contains(&i) => DisplayPointStatus::Obstacle,
+                PointStatus::Occupied => DisplayPointStatus::Occupied,
+                _ => DisplayPointStatus::Unoccupied,
             })
             .collect();
 
         // Overlay the currently selected block, taking into account the user's cursor position.
+        // A puzzle can end with nothing left in the hand.
         let mut has_conflicts = false;
-        for p in self.blocks[self.selected.current()].coordinates() {
+        let selected = self.session.hand().get(self.selected.current());
+        for p in selected.into_iter().flat_map(|block| block.coordinates()) {
             let index = ((p.y + self.cursor_position.y) * self.board_width
                 + (p.x + self.cursor_position.x)) as usize;
 
             display_coords[index] = match display_coords[index] {
-                DisplayPointStatus::Occupied => {
+                DisplayPointStatus::Occupied | DisplayPointStatus::Obstacle => {
                     has_conflicts = true;
                     DisplayPointStatus::Hovered { has_conflict: true }
                 }
@@ -357,12 +1323,25 @@ impl App {
             }
         }
 
+        // Show where the hint suggests the block goes, wherever the selected block isn't.
+        if let Some(hint) = &self.hint
+            && let Some(block) = self.session.hand().get(hint.selected)
+        {
+            for p in block.coordinates() {
+                let index = ((p.y + hint.row) * self.board_width + (p.x + hint.column)) as usize;
+                if let Some(DisplayPointStatus::Unoccupied) = display_coords.get(index) {
+                    display_coords[index] = DisplayPointStatus::Hinted;
+                }
+            }
+        }
+
         // If there are no conflicts, show any lines that would be blasted if the block were placed.
         if !has_conflicts {
             'row_loop: for row in 0..self.board_height {
                 for column in 0..self.board_width {
                     let index = (row * self.board_width + column) as usize;
                     if let DisplayPointStatus::Unoccupied
+                    | DisplayPointStatus::Hinted
                     | DisplayPointStatus::Hovered { has_conflict: true } = display_coords[index]
                     {
                         continue 'row_loop;
@@ -371,7 +1350,9 @@ impl App {
 
                 for column in 0..self.board_width {
                     let index = (row * self.board_width + column) as usize;
-                    display_coords[index] = DisplayPointStatus::Blast;
+                    if !obstacles.contains(&index) {
+                        display_coords[index] = DisplayPointStatus::Blast;
+                    }
                 }
             }
 
@@ -379,6 +1360,7 @@ impl App {
                 for row in 0..self.board_height {
                     let index = (row * self.board_width + column) as usize;
                     if let DisplayPointStatus::Unoccupied
+                    | DisplayPointStatus::Hinted
                     | DisplayPointStatus::Hovered { has_conflict: true } = display_coords[index]
                     {
                         continue 'column_loop;
@@ -387,78 +1369,152 @@ impl App {
 
                 for row in 0..self.board_height {
                     let index = (row * self.board_width + column) as usize;
-                    display_coords[index] = DisplayPointStatus::Blast;
+                    if !obstacles.contains(&index) {
+                        display_coords[index] = DisplayPointStatus::Blast;
+                    }
                 }
             }
         }
 
-        // Render the game board.
-        for (i, row) in game_rows.iter().rev().enumerate() {
-            let game_cols =
-                Layout::horizontal(vec![Constraint::default(); self.board_width as usize])
-                    .vertical_margin(0)
-                    .split(*row);
-
-            for (j, col) in game_cols.iter().enumerate() {
-                let repr = match display_coords[i * self.board_width as usize + j] {
-                    DisplayPointStatus::Blast => Text::from(BLOCK_REPRESENTATION).yellow(),
-                    DisplayPointStatus::Occupied => Text::from(BLOCK_REPRESENTATION).blue(),
-                    DisplayPointStatus::Unoccupied => {
-                        Text::from(EMPTY_BLOCK_REPRESENTATION).dark_gray()
+        // Lines that were just cleared flash and fade, wherever nothing has been put since.
+        let clear_frame = self.clearing.as_ref().map(|clearing| {
+            for &index in clearing.cells() {
+                if let Some(DisplayPointStatus::Unoccupied) = display_coords.get(index) {
+                    display_coords[index] = DisplayPointStatus::Clearing;
+                }
+            }
+            clearing.frame()
+        });
+
+        // Render the game board, filling bigger squares with more of each glyph.
+        let cells = layout.board_cells(self.board_height as u16, self.board_width as u16);
+        let glyphs = &self.config.glyphs;
+        for (cell, status) in cells.iter().copied().zip(display_coords) {
+            let (glyph, style) = match status {
+                DisplayPointStatus::Blast => (glyphs.block.as_str(), self.theme.blast),
+                DisplayPointStatus::Occupied => (glyphs.block.as_str(), self.theme.occupied),
+                DisplayPointStatus::Unoccupied => (glyphs.empty.as_str(), self.theme.empty),
+                DisplayPointStatus::Obstacle => (glyphs.obstacle.as_str(), self.theme.obstacle),
+                DisplayPointStatus::Hinted => (glyphs.block.as_str(), self.theme.hint),
+                DisplayPointStatus::Clearing => match clear_frame {
+                    Some(ClearFrame::Flash { lit: true }) => {
+                        (glyphs.block.as_str(), self.theme.blast)
                     }
-                    DisplayPointStatus::Hovered {
-                        has_conflict: false,
-                    } => Text::from(BLOCK_REPRESENTATION).magenta(),
-                    DisplayPointStatus::Hovered { has_conflict: true } => Text::from("◎").red(),
-                };
+                    Some(ClearFrame::Flash { lit: false }) => {
+                        (glyphs.block.as_str(), self.theme.occupied)
+                    }
+                    Some(ClearFrame::Fade(step)) => {
+                        (animation::FADE_GLYPHS[step], self.theme.blast)
+                    }
+                    _ => (glyphs.empty.as_str(), self.theme.empty),
+                },
+                DisplayPointStatus::Hovered {
+                    has_conflict: false,
+                } => (glyphs.block.as_str(), self.theme.hovered),
+                DisplayPointStatus::Hovered { has_conflict: true } => {
+                    (glyphs.conflict.as_str(), self.theme.conflict)
+                }
+            };
 
-                // FIXME: game over screen isnt my favorite.
-                Paragraph::new(|| -> Text<'_> {
-                    if self.game_over { repr.gray() } else { repr }
-                }())
-                .centered()
-                .render(*col, buf);
-            }
-        }
-    }
+            // leave a column between squares, and a line between rows once they're tall enough.
+            let across = ((cell.width - 1) as usize / Span::raw(glyph).width().max(1)).max(1);
+            let down = cell.height.saturating_sub(1).max(1);
+            let repr = Text::from(vec![Line::from(glyph.repeat(across)); down as usize])
+                .patch_style(style);
 
-    fn render_block_selector(&self, area: Rect, buf: &mut Buffer) {
-        // remaining blocks view
-        let block_areas = Layout::horizontal([
-            Constraint::Percentage(23), // spacing
-            Constraint::Percentage(18),
-            Constraint::Percentage(18),
-            Constraint::Percentage(18),
-            Constraint::Percentage(23), // spacing
-        ])
-        .flex(Flex::Center)
-        .split(area);
+            // FIXME: game over screen isnt my favorite.
+            Paragraph::new(if self.is_game_over() {
+                repr.patch_style(self.theme.game_over)
+            } else {
+                repr
+            })
+            .centered()
+            .render(cell, buf);
+        }
 
-        // account for spacing
-        let offset = 1;
-        for (i, b) in self.blocks.iter().enumerate() {
-            let mut view = Text::from(format!("{}", b));
+        // Show what the last placement earned over the squares it cleared, drifting upwards.
+        if let Some(award) = &self.award
+            && let Some(cell) = cells.get(award.anchor())
+        {
+            let rise = match self.config.layout.animations {
+                true => award.rise(),
+                false => 0,
+            };
+            let width = (Span::raw(award.text()).width() as u16).min(buf.area.width);
+            let x = (cell.x + cell.width / 2)
+                .saturating_sub(width / 2)
+                .min(buf.area.right() - width);
+            let y = cell.y.saturating_sub(rise).max(layout.board.y);
+            Paragraph::new(award.text())
+                .style(self.theme.blast.add_modifier(Modifier::BOLD))
+                .render(Rect::new(x, y, width, 1), buf);
+        }
+    }
 
-            // add a border to the selected block
-            view = if i == self.selected.current() {
-                view.magenta()
+    fn render_block_selector(&self, layout: &ScreenLayout, buf: &mut Buffer) {
+        let slots = layout.hand_slots(self.session.blocks_per_turn() as u16);
+        for (i, b) in self.session.hand().iter().enumerate() {
+            let mut view = Text::from(
+                format!("{}", b).replace(HAND_BLOCK_REPRESENTATION, &self.config.glyphs.hand),
+            );
+
+            // grey out blocks that can't go anywhere, and add a border to the selected block
+            let playable = self.session.is_playable(i);
+            view = if !playable {
+                view.patch_style(self.theme.unplayable)
+            } else if i == self.selected.current() {
+                view.patch_style(self.theme.selected_block)
+            } else if self.hint.is_some_and(|h| h.selected == i) {
+                view.patch_style(self.theme.hint)
             } else {
-                view.black()
+                view.patch_style(self.theme.unselected_block)
             };
 
-            if i == self.selected.current() {
+            if playable && i == self.selected.current() {
                 Paragraph::new(view)
                     .style(Style::default().add_modifier(Modifier::SLOW_BLINK))
                     .centered()
-                    .render(block_areas[i + offset], buf);
+                    .render(slots[i], buf);
             } else {
-                Paragraph::new(view)
-                    .centered()
-                    .render(block_areas[i + offset], buf);
+                Paragraph::new(view).centered().render(slots[i], buf);
             }
         }
     }
 
+    /// Say how big the terminal needs to be, in place of a garbled game.
+    fn render_too_small(&self, area: Rect, buf: &mut Buffer) {
+        let (width, height) = self.screen_contents().minimum_size();
+
+        let block = Block::bordered()
+            .border_set(border::THICK)
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let message = Text::from(vec![
+            Line::from("Terminal too small").patch_style(self.theme.warning),
+            Line::from(format!(
+                "Needs at least {}x{}, this one is {}x{}",
+                width, height, area.width, area.height
+            ))
+            .patch_style(self.theme.scoreboard),
+            Line::from(vec![
+                " Quit ".into(),
+                Span::styled(
+                    format!("<{}> ", self.keymap.describe(&[Action::Quit])),
+                    self.theme.key_hint,
+                ),
+            ]),
+        ]);
+        let [message_area] = Layout::vertical([Constraint::Length(message.height() as u16)])
+            .flex(Flex::Center)
+            .areas(inner);
+        Paragraph::new(message)
+            .centered()
+            .wrap(Wrap { trim: true })
+            .render(message_area, buf);
+    }
+
     fn exit(&mut self) {
         self.exit = true;
     }
@@ -466,92 +1522,366 @@ impl App {
 
 impl Widget for &App {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let debug_area_constraint = Constraint::Percentage(56);
-        let block_selector_constraint = Constraint::Percentage(24);
-        let scoreboard_constraint = Constraint::Percentage(10);
-        let vspace_constraint = Constraint::Percentage(10);
-        let game_board_constraint = Constraint::Min(self.board_height as u16);
-
-        // split the screen in horizontal slices
-        let top_to_bot_view_areas = Layout::vertical([
-            scoreboard_constraint,
-            vspace_constraint,
-            game_board_constraint,
-            vspace_constraint,
-            block_selector_constraint,
-            debug_area_constraint,
-        ])
-        .vertical_margin(5)
-        .flex(Flex::Center)
-        .split(area);
-
-        let [local_scoreboard_area, _, _global_scoreboard_area] = Layout::horizontal([
-            Constraint::Ratio(1, 3),
-            Constraint::Ratio(1, 3),
-            Constraint::Ratio(1, 3),
-        ])
-        .horizontal_margin(5)
-        .areas(top_to_bot_view_areas[0]);
-
-        self.render_local_scoreboard(local_scoreboard_area, buf);
+        let Some(layout) = self.screen_layout(area) else {
+            self.render_too_small(area, buf);
+            return;
+        };
+        let [local_scoreboard_area, assisted_scoreboard_area] = layout.scoreboards;
+
+        if self.daily.is_some() {
+            if self.config.layout.show_scoreboard {
+                self.render_daily_scoreboard(local_scoreboard_area, buf);
+                self.render_daily_status(assisted_scoreboard_area, buf);
+            }
+        } else if self.config.layout.show_scoreboard && self.puzzle.is_none() {
+            self.render_local_scoreboard(
+                "Personal Best",
+                &self.scoreboard,
+                local_scoreboard_area,
+                buf,
+            );
+
+            // only bother showing assisted scores to players who use undo
+            if self.assisted || !self.assisted_scoreboard.all().is_empty() {
+                self.render_local_scoreboard(
+                    "Assisted Best",
+                    &self.assisted_scoreboard,
+                    assisted_scoreboard_area,
+                    buf,
+                );
+            }
+        }
         // todo
         // self.render_global_scoreboard(global_scoreboard_area, buf);
-        self.render_game_board(top_to_bot_view_areas[2], buf);
-        self.render_block_selector(top_to_bot_view_areas[4], buf);
+        self.render_game_board(&layout, buf);
+        self.render_block_selector(&layout, buf);
 
         // Warn the user when attempting invalid block placement
         if self.show_conflict_popup {
-            Clear.render(top_to_bot_view_areas[1], buf);
-            let conflict_inner = Text::from("It doesn't fit!").red();
-            let conflict_outer = Paragraph::new(conflict_inner).centered();
-            let popup_area = create_popup_area(area, 60, 80);
-            conflict_outer.render(popup_area, buf);
+            Clear.render(layout.warning, buf);
+            Paragraph::new(Text::from("It doesn't fit!").patch_style(self.theme.warning))
+                .centered()
+                .render(layout.warning, buf);
         }
 
-        // Game Over - clear everything except the game board.
-        if self.game_over {
-            Clear.render(top_to_bot_view_areas[0], buf);
-            Clear.render(top_to_bot_view_areas[3], buf);
-            Clear.render(top_to_bot_view_areas[4], buf);
-            Clear.render(top_to_bot_view_areas[5], buf);
+        if let Some(playback) = &self.playback {
+            self.render_playback_status(playback, layout.messages, buf);
+        }
+        if self.demo.is_some() {
+            Paragraph::new(Text::from("DEMO - press any key to play"))
+                .style(self.theme.scoreboard)
+                .centered()
+                .render(layout.messages, buf);
+        }
+        if let Some(puzzle) = &self.puzzle {
+            Paragraph::new(Text::from(puzzle.description.as_str()))
+                .style(self.theme.scoreboard)
+                .centered()
+                .render(layout.messages, buf);
+        }
 
-            let game_over_str = Text::from(format!("{}", "GAME OVER")).red();
+        // Offer to continue a saved game in place of the block selector.
+        if self.show_resume_prompt && self.demo.is_none() {
+            Clear.render(layout.hand, buf);
+            let prompt = Text::from(vec![
+                Line::from("Continue your last game?"),
+                Line::from(""),
+                Line::from(vec![
+                    " Continue ".into(),
+                    Span::styled(
+                        format!("<{}> ", self.keymap.describe(&[Action::Continue])),
+                        self.theme.key_hint,
+                    ),
+                    " New Game ".into(),
+                    Span::styled(
+                        format!("<{}> ", self.keymap.describe(&[Action::NewGame])),
+                        self.theme.key_hint,
+                    ),
+                ]),
+            ]);
+            Paragraph::new(prompt).centered().render(layout.hand, buf);
+        }
+
+        // Game Over - clear everything except the game board.
+        if self.is_game_over() {
+            for scoreboard in layout.scoreboards {
+                Clear.render(scoreboard, buf);
+            }
+            Clear.render(layout.hand, buf);
+            Clear.render(layout.messages, buf);
+
+            let breakdown = self.scorer.breakdown();
+            let game_over_str = match &self.puzzle {
+                Some(puzzle) => Text::from(vec![
+                    Line::from(match (self.is_solved(), self.moves) {
+                        (true, 1) => "SOLVED in 1 move!".to_string(),
+                        (true, moves) => format!("SOLVED in {} moves!", moves),
+                        (false, _) => "NOT SOLVED".to_string(),
+                    })
+                    .patch_style(self.theme.warning),
+                    Line::from(puzzle.to_string()).patch_style(self.theme.scoreboard),
+                ]),
+                None => Text::from(vec![
+                    Line::from("GAME OVER").patch_style(self.theme.warning),
+                    Line::from(format!(
+                        "Lines {}  Multi-line {}  Combos {}  Board clears {}  Best combo x{}",
+                        breakdown.lines,
+                        breakdown.multi_line,
+                        breakdown.combo,
+                        breakdown.board_clears,
+                        breakdown.best_combo
+                    ))
+                    .patch_style(self.theme.scoreboard),
+                ]),
+            };
+            // wrapped, as the warning area is narrow beside the board.
             Paragraph::new(game_over_str)
                 .centered()
-                .render(top_to_bot_view_areas[1], buf);
-
-            let help_txt = Text::from(format!("{}", "Press ENTER to play again.")).blue();
+                .wrap(Wrap { trim: true })
+                .render(layout.warning, buf);
+
+            let help_txt = match &self.name_entry {
+                Some(name) => Text::from(vec![
+                    Line::from(if self.daily.is_some() {
+                        "Enter your name for today's scoreboard:"
+                    } else {
+                        "New high score! Enter your name:"
+                    }),
+                    Line::from(format!("{}_", name)),
+                ])
+                .patch_style(self.theme.scoreboard),
+                None if self.puzzle.is_some() => Text::from(format!(
+                    "Press {} to try again or {} for the puzzle list.",
+                    self.keymap.describe(&[Action::NewGame]).to_uppercase(),
+                    self.keymap.describe(&[Action::Quit]).to_uppercase()
+                ))
+                .patch_style(self.theme.key_hint),
+                None => Text::from(format!(
+                    "Press {} to play again.",
+                    self.keymap.describe(&[Action::NewGame]).to_uppercase()
+                ))
+                .patch_style(self.theme.key_hint),
+            };
             Paragraph::new(help_txt)
                 .centered()
-                .render(top_to_bot_view_areas[5], buf);
-        }
-
-        let title = Line::from(" Block TUI ".bold());
-        let score = Line::from(format!(" Current Score: {} ", self.game.score).bold());
-        let instructions = Line::from(vec![
-            " Quit ".into(),
-            "<q> ".blue().bold(),
-            " Movement ".into(),
-            "<h,j,k,l> ".blue().bold(),
-            " Cycle Block Selection ".into(),
-            "<n> ".blue().bold(),
-            " Place Block ".into(),
-            "<Space> ".blue().bold(),
-        ]);
-        let block = Block::bordered()
+                .wrap(Wrap { trim: true })
+                .render(layout.messages, buf);
+        }
+
+        let title = match &self.daily {
+            _ if self.playback.is_some() => Line::from(" Block TUI - Replay ".bold()),
+            _ if self.demo.is_some() => Line::from(" Block TUI - Demo ".bold()),
+            Some(daily) => Line::from(format!(" Block TUI - Daily {} ", daily.date()).bold()),
+            None if self.puzzle.is_some() => {
+                let name = self
+                    .puzzle
+                    .as_ref()
+                    .map(|p| p.name.as_str())
+                    .unwrap_or_default();
+                Line::from(format!(" Puzzle: {} ", name).bold())
+            }
+            None if self.mode != GameMode::Endless => {
+                Line::from(format!(" Block TUI - {} ", self.mode.title()).bold())
+            }
+            None => Line::from(" Block TUI ".bold()),
+        };
+        let mut notes = Vec::new();
+        // the notes are about the player's game, not the demo's.
+        if self.demo.is_none() {
+            if let Some(left) = self.time_left() {
+                // round up, so the clock reads 0:00 only once time is up.
+                let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+                notes.push(format!("{}:{:02} left", seconds / 60, seconds % 60));
+            }
+            if let Some(left) = self.moves_left() {
+                notes.push(format!(
+                    "{} move{} left",
+                    left,
+                    if left == 1 { "" } else { "s" }
+                ));
+            }
+            if self.assisted {
+                notes.push("assisted".to_string());
+            } else if self.daily.is_some() && self.practice {
+                notes.push("practice".to_string());
+            }
+            if self.hints_used > 0 {
+                notes.push(format!(
+                    "{} hint{}",
+                    self.hints_used,
+                    if self.hints_used == 1 { "" } else { "s" }
+                ));
+            }
+        }
+        let score = match notes.is_empty() {
+            true => Line::from(format!(" Current Score: {} ", self.score()).bold()),
+            false => Line::from(
+                format!(" Current Score: {} ({}) ", self.score(), notes.join(", ")).bold(),
+            ),
+        };
+        let mut help: Vec<(&str, Vec<Action>)> = if self.playback.is_some() {
+            vec![
+                (" Quit ", vec![Action::Quit]),
+                (" Play/Pause ", vec![Action::Place]),
+                (" Step ", vec![Action::MoveLeft, Action::MoveRight]),
+                (" Speed ", vec![Action::MoveDown, Action::MoveUp]),
+            ]
+        } else {
+            vec![
+                (" Quit ", vec![Action::Quit]),
+                (
+                    " Movement ",
+                    vec![
+                        Action::MoveLeft,
+                        Action::MoveDown,
+                        Action::MoveUp,
+                        Action::MoveRight,
+                    ],
+                ),
+                (
+                    " Cycle Block Selection ",
+                    vec![Action::CycleNext, Action::CyclePrevious],
+                ),
+                (" Place Block ", vec![Action::Place]),
+                (" Undo/Redo ", vec![Action::Undo, Action::Redo]),
+                (" Hint ", vec![Action::Hint]),
+            ]
+        };
+        if !self.allow_undo {
+            help.retain(|(_, actions)| !actions.contains(&Action::Undo));
+        }
+        let mut help: Vec<(&str, String)> = help
+            .into_iter()
+            .map(|(label, actions)| (label, self.keymap.describe(&actions)))
+            .collect();
+        if self.playback.is_some() {
+            help.push((" Seek ", "Home,End,0-9".to_string()));
+        }
+
+        // leave out whatever doesn't fit along the bottom border, rather than cutting it off.
+        let mut instructions = Line::default();
+        for (label, keys) in help {
+            let keys = format!("<{}> ", keys);
+            if instructions.width() + label.len() + keys.chars().count() + 4 > area.width as usize {
+                break;
+            }
+            instructions.push_span(Span::from(label));
+            instructions.push_span(Span::styled(keys, self.theme.key_hint));
+        }
+        // a puzzle isn't dealt from a seed, its goal is more use.
+        let details = match &self.puzzle {
+            Some(puzzle) => format!(" {} ", puzzle),
+            None if !self.board.is_standard() && self.playback.is_none() => {
+                format!(" Board: {}  Seed: {} ", self.board, self.session.seed())
+            }
+            None => format!(" Seed: {} ", self.session.seed()),
+        };
+        let mut block = Block::bordered()
             .title(title.left_aligned())
             .title(score.centered())
-            .title_bottom(instructions.centered())
+            .title(Line::from(details).right_aligned())
             .border_set(border::THICK)
             .border_type(BorderType::Rounded);
+        if self.demo.is_some() {
+            instructions = Line::from(" Press any key to play ");
+        }
+        if self.config.layout.show_help {
+            block = block.title_bottom(instructions.centered());
+        }
         Paragraph::default().block(block).render(area, buf);
     }
 }
 
-fn create_popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
-    let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(Flex::Center);
-    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
-    let [area] = vertical.areas(area);
-    let [area] = horizontal.areas(area);
-    area
+/// The seed every game should be dealt from: the daily challenge's, or the one the player asked
+/// for, if any.
+fn seed(config: &Config, daily: Option<&DailyChallenge>) -> Option<u64> {
+    match daily {
+        Some(daily) => Some(daily.seed()),
+        None => config.game.seed,
+    }
+}
+
+/// A generator for a new game, using the seed the player asked for if there is one.
+fn new_generator(seed: Option<u64>) -> BlockGenerator {
+    match seed {
+        Some(seed) => BlockGenerator::new(seed),
+        None => BlockGenerator::random(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use jcblocks::block::Block;
+    use ratatui::{Terminal, backend::TestBackend};
+
+    use super::*;
+
+    #[test]
+    fn resumed_rotated_line_is_drawn_on_the_board() {
+        let db_path =
+            std::env::temp_dir().join(format!("jcblocktui-app-test-{}.db", std::process::id()));
+        let mut line = Block::line(5);
+        for _ in 0..3 {
+            line.rotate_left();
+        }
+        let session = Session::resume(Game::default(), vec![line], BlockGenerator::new(1), 3);
+        LocalSaveSlot::new(&db_path)
+            .unwrap()
+            .store(&SavedGame::capture(
+                &session,
+                &BlockIndex::new(1),
+                &Scorer::default(),
+                GameMode::Endless,
+                0,
+                false,
+                0,
+            ))
+            .unwrap();
+
+        let mut app = App::new(&Config::default(), &db_path).unwrap();
+        std::fs::remove_file(&db_path).unwrap();
+        assert!(app.show_resume_prompt);
+        assert_eq!(app.clamped(&app.cursor_position), app.cursor_position);
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal
+            .draw(|frame| frame.render_widget(&app, frame.area()))
+            .unwrap();
+
+        // the prompt goes through the key map like any other key.
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('c')))
+            .unwrap();
+        assert!(!app.show_resume_prompt);
+    }
+
+    #[test]
+    fn quitting_blitz_and_zen_records_the_score() {
+        // the scoreboard, save slot and replays all go in a data directory of the test's own.
+        let data_dir =
+            std::env::temp_dir().join(format!("jcblocktui-app-modes-test-{}", std::process::id()));
+        // SAFETY: no other test reads the environment or the data directory.
+        unsafe { std::env::set_var(paths::DATA_DIR_ENV, &data_dir) };
+        let db_path = paths::default_db_path().unwrap();
+        for mode in [GameMode::Blitz, GameMode::Zen] {
+            let mut config = Config::default();
+            config.game.mode = mode;
+            config.player.name = Some("Allison".to_string());
+            let mut app = App::new(&config, &db_path).unwrap();
+            app.place().unwrap();
+            app.handle_key_event(KeyEvent::from(KeyCode::Char('q')))
+                .unwrap();
+            assert!(app.exit);
+
+            let scoreboard = LocalScoreBoard::for_mode(5, &db_path, mode, false).unwrap();
+            assert_eq!(scoreboard.all().len(), 1);
+            assert_eq!(scoreboard.all()[0].name, "Allison");
+        }
+        assert_eq!(
+            std::fs::read_dir(paths::replay_dir().unwrap())
+                .unwrap()
+                .count(),
+            2
+        );
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
 }
@@ -0,0 +1,301 @@
+use std::fmt::Write;
+
+use anyhow::Result;
+use rand::{SeedableRng, seq::IndexedRandom};
+use rand_chacha::ChaCha8Rng;
+
+use crate::bot;
+use crate::generator::BlockGenerator;
+use crate::session::{Move, Player, Session};
+
+/// The ways the computer can play a benchmark game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Any legal move.
+    Random,
+    /// Whichever move clears the most lines right now, the first one found on a tie.
+    GreedyLines,
+    /// The first move of the best plan for the whole hand, as used for hints and the demo.
+    Lookahead,
+}
+
+impl Strategy {
+    pub const NAMES: [&'static str; 3] = ["random", "greedy-lines", "lookahead"];
+    pub const ALL: [Strategy; 3] = [Strategy::Random, Strategy::GreedyLines, Strategy::Lookahead];
+
+    pub fn by_name(name: &str) -> Option<Strategy> {
+        Strategy::ALL.into_iter().find(|s| s.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strategy::Random => "random",
+            Strategy::GreedyLines => "greedy-lines",
+            Strategy::Lookahead => "lookahead",
+        }
+    }
+
+    /// A player using this strategy. Random choices are made from `seed`, so a benchmark can be
+    /// run again with the same results.
+    pub fn player(&self, seed: u64) -> Box<dyn Player> {
+        match self {
+            Strategy::Random => Box::new(RandomPlayer(ChaCha8Rng::seed_from_u64(seed))),
+            Strategy::GreedyLines => Box::new(greedy_lines),
+            Strategy::Lookahead => Box::new(LookaheadPlayer(Vec::new())),
+        }
+    }
+}
+
+struct RandomPlayer(ChaCha8Rng);
+
+impl Player for RandomPlayer {
+    fn choose(&mut self, session: &Session) -> Option<Move> {
+        session.legal_moves().choose(&mut self.0).copied()
+    }
+}
+
+fn greedy_lines(session: &Session) -> Option<Move> {
+    let mut best: Option<(Move, usize)> = None;
+    for m in session.legal_moves() {
+        let mut after = session.clone();
+        let Ok(placed) = after.apply(&m) else {
+            continue;
+        };
+        if best.is_none_or(|(_, lines)| placed.lines_cleared > lines) {
+            best = Some((m, placed.lines_cleared));
+        }
+    }
+
+    best.map(|(m, _)| m)
+}
+
+/// Plans once per hand and follows the plan, as planning is the slow part.
+struct LookaheadPlayer(Vec<Move>);
+
+impl Player for LookaheadPlayer {
+    fn choose(&mut self, session: &Session) -> Option<Move> {
+        if self.0.is_empty() || !session.is_legal(&self.0[0]) {
+            self.0 = bot::plan(session)?.moves;
+        }
+
+        Some(self.0.remove(0))
+    }
+}
+
+/// How one benchmark game went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    pub seed: u64,
+    pub score: usize,
+    /// Hands played to the end.
+    pub turns: usize,
+    /// The game ended before reaching the turn limit.
+    pub failed: bool,
+}
+
+/// Play one game dealt from `seed`, stopping after `max_turns` hands if it gets that far.
+pub fn play(
+    strategy: Strategy,
+    seed: u64,
+    blocks_per_turn: usize,
+    max_turns: usize,
+) -> Result<GameResult> {
+    let mut session = Session::new(BlockGenerator::new(seed), blocks_per_turn);
+    let mut player = strategy.player(seed);
+
+    let mut turns = 0;
+    while !session.is_game_over() && turns < max_turns {
+        let Some(m) = player.choose(&session) else {
+            break;
+        };
+        if session.apply(&m)?.dealt {
+            turns += 1;
+        }
+    }
+
+    Ok(GameResult {
+        seed,
+        score: session.score(),
+        turns,
+        failed: turns < max_turns,
+    })
+}
+
+/// The results of playing a run of seeds with one strategy.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub strategy: Strategy,
+    pub games: Vec<GameResult>,
+}
+
+impl Summary {
+    /// Play `games` games with consecutive seeds from `first_seed`.
+    pub fn run(
+        strategy: Strategy,
+        first_seed: u64,
+        games: usize,
+        blocks_per_turn: usize,
+        max_turns: usize,
+    ) -> Result<Summary> {
+        let games = (0..games as u64)
+            .map(|i| {
+                play(
+                    strategy,
+                    first_seed.wrapping_add(i),
+                    blocks_per_turn,
+                    max_turns,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Summary { strategy, games })
+    }
+
+    pub fn mean(&self) -> f64 {
+        mean(self.games.iter().map(|g| g.score))
+    }
+
+    pub fn median(&self) -> f64 {
+        let mut scores: Vec<usize> = self.games.iter().map(|g| g.score).collect();
+        scores.sort_unstable();
+        match scores.len() {
+            0 => 0.0,
+            n if n % 2 == 0 => (scores[n / 2 - 1] + scores[n / 2]) as f64 / 2.0,
+            n => scores[n / 2] as f64,
+        }
+    }
+
+    pub fn max(&self) -> usize {
+        self.games.iter().map(|g| g.score).max().unwrap_or(0)
+    }
+
+    /// How many games were played up to and including the first to end before the turn limit.
+    pub fn games_to_first_fail(&self) -> Option<usize> {
+        self.games.iter().position(|g| g.failed).map(|i| i + 1)
+    }
+
+    pub fn mean_turns(&self) -> f64 {
+        mean(self.games.iter().map(|g| g.turns))
+    }
+}
+
+fn mean(values: impl ExactSizeIterator<Item = usize>) -> f64 {
+    match values.len() {
+        0 => 0.0,
+        n => values.sum::<usize>() as f64 / n as f64,
+    }
+}
+
+const HEADINGS: [&str; 7] = [
+    "strategy",
+    "games",
+    "mean",
+    "median",
+    "max",
+    "first fail",
+    "mean turns",
+];
+
+fn columns(summary: &Summary) -> [String; 7] {
+    [
+        summary.strategy.name().to_string(),
+        summary.games.len().to_string(),
+        format!("{:.1}", summary.mean()),
+        format!("{:.1}", summary.median()),
+        summary.max().to_string(),
+        summary
+            .games_to_first_fail()
+            .map(|n| n.to_string())
+            .unwrap_or_default(),
+        format!("{:.1}", summary.mean_turns()),
+    ]
+}
+
+/// Lay the summaries out in aligned columns for reading.
+pub fn table(summaries: &[Summary]) -> String {
+    let rows: Vec<[String; 7]> = summaries.iter().map(columns).collect();
+    let widths: Vec<usize> = (0..HEADINGS.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].len())
+                .chain([HEADINGS[i].len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+    let headings = HEADINGS.map(String::from);
+    for row in std::iter::once(&headings).chain(rows.iter()) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths.iter())
+            .enumerate()
+            // the strategy name reads best on the left, numbers on the right.
+            .map(|(i, (cell, width))| match i {
+                0 => format!("{cell:<width$}"),
+                _ => format!("{cell:>width$}"),
+            })
+            .collect();
+        let _ = writeln!(out, "{}", cells.join("  "));
+    }
+
+    out
+}
+
+/// The summaries as CSV, with a header row.
+pub fn csv(summaries: &[Summary]) -> String {
+    let mut out = HEADINGS.map(|h| h.replace(' ', "_")).join(",");
+    out.push('\n');
+    for summary in summaries {
+        out.push_str(&columns(summary).join(","));
+        out.push('\n');
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_seeds_same_results() {
+        for strategy in [Strategy::Random, Strategy::GreedyLines] {
+            let a = Summary::run(strategy, 5, 3, 3, 50).unwrap();
+            let b = Summary::run(strategy, 5, 3, 3, 50).unwrap();
+            assert_eq!(a.games, b.games);
+            assert_eq!(a.games[1].seed, 6);
+        }
+    }
+
+    #[test]
+    fn summary_statistics() {
+        let game = |score, turns, failed| GameResult {
+            seed: 0,
+            score,
+            turns,
+            failed,
+        };
+        let summary = Summary {
+            strategy: Strategy::Random,
+            games: vec![
+                game(300, 10, false),
+                game(100, 5, true),
+                game(0, 1, true),
+                game(200, 10, false),
+            ],
+        };
+
+        assert_eq!(summary.mean(), 150.0);
+        assert_eq!(summary.median(), 150.0);
+        assert_eq!(summary.max(), 300);
+        assert_eq!(summary.games_to_first_fail(), Some(2));
+        assert_eq!(summary.mean_turns(), 6.5);
+        assert_eq!(
+            csv(&[summary]),
+            "strategy,games,mean,median,max,first_fail,mean_turns\n\
+             random,4,150.0,150.0,300,2,6.5\n"
+        );
+    }
+}
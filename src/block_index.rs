@@ -3,59 +3,122 @@ use super::config::NUM_BLOCKS_PER_TURN;
 pub enum DisplayPointStatus {
     Occupied,
     Unoccupied,
-    Hovered { has_conflict: bool },
+    Hovered {
+        has_conflict: bool,
+    },
+    Hinted,
     Blast,
+    /// Part of a line that was just cleared.
+    Clearing,
+    /// A square that can't be cleared.
+    Obstacle,
 }
 
-#[derive(Debug)]
+/// Which block in the hand is selected.
+///
+/// Placed blocks leave the hand and the ones after them move down a slot, so the selection is
+/// kept within the blocks that are actually left.
+#[derive(Debug, Clone)]
 pub struct BlockIndex {
-    val: usize,
-    num_left: usize,
+    current: usize,
+    remaining: usize,
 }
 
 impl BlockIndex {
-    /// Construct a new block index.
-    pub fn new(val: usize) -> Self {
+    /// Select the first block of a hand of `remaining` blocks.
+    pub fn new(remaining: usize) -> Self {
+        BlockIndex {
+            current: 0,
+            remaining,
+        }
+    }
+
+    /// Resume selection of `current` in a hand that still has `remaining` blocks, or the last of
+    /// them if there are fewer.
+    pub fn restore(current: usize, remaining: usize) -> Self {
         BlockIndex {
-            val,
-            num_left: NUM_BLOCKS_PER_TURN - 1,
+            current: current.min(remaining.saturating_sub(1)),
+            remaining,
         }
     }
 
-    /// Retrieve the contained value.
+    /// Retrieve the selected slot.
     pub fn current(&self) -> usize {
-        self.val
+        self.current
     }
 
-    /// Retrieve the contained value and decrement the internal counter.
-    pub fn place(&mut self) -> usize {
-        let prev = self.val;
-        self.num_left = if self.num_left == 0 {
-            NUM_BLOCKS_PER_TURN - 1
-        } else {
-            self.num_left - 1
-        };
-        self.val = if self.val == 0 { 0 } else { self.val - 1 };
-        prev
+    /// How many blocks are left to choose from.
+    pub fn remaining(&self) -> usize {
+        self.remaining
     }
 
-    /// Get the next valid value.
-    pub fn cycle(&mut self) -> &mut Self {
-        if self.val == self.num_left {
-            self.val = 0;
-        } else {
-            self.val += 1;
+    /// Select the block in `slot`, returning false if there isn't one.
+    pub fn select(&mut self, slot: usize) -> bool {
+        if slot >= self.remaining {
+            return false;
         }
 
-        self
+        self.current = slot;
+        true
+    }
+
+    /// Select the next block, going around to the first after the last.
+    pub fn next(&mut self) {
+        self.current = (self.current + 1) % self.remaining.max(1);
+    }
+
+    /// Select the previous block, going around to the last before the first.
+    pub fn previous(&mut self) {
+        self.current = match self.current {
+            0 => self.remaining.saturating_sub(1),
+            n => n - 1,
+        };
+    }
+
+    /// The selected block was placed, leaving `remaining` blocks in the hand.
+    ///
+    /// The block after it moves up into the selected slot, unless it was the last one. Once a new
+    /// hand is dealt the first block is selected.
+    pub fn placed(&mut self, remaining: usize) {
+        self.current = match remaining >= self.remaining {
+            true => 0,
+            false => self.current.min(remaining.saturating_sub(1)),
+        };
+        self.remaining = remaining;
     }
 }
 
 impl Default for BlockIndex {
     fn default() -> Self {
-        BlockIndex {
-            val: 0,
-            num_left: NUM_BLOCKS_PER_TURN - 1,
-        }
+        BlockIndex::new(NUM_BLOCKS_PER_TURN)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn selection_follows_the_remaining_blocks() {
+        let mut selected = BlockIndex::new(3);
+        selected.previous();
+        assert_eq!(selected.current(), 2);
+        selected.next();
+        assert_eq!(selected.current(), 0);
+        assert!(!selected.select(3));
+        assert!(selected.select(1));
+
+        // the last block moves up into the placed one's slot.
+        selected.placed(2);
+        assert_eq!(selected.current(), 1);
+        selected.placed(1);
+        assert_eq!(selected.current(), 0);
+        selected.next();
+        assert_eq!(selected.current(), 0);
+
+        // a new hand is dealt.
+        selected.placed(3);
+        assert_eq!((selected.current(), selected.remaining()), (0, 3));
+        assert_eq!(BlockIndex::restore(4, 2).current(), 1);
     }
 }
@@ -0,0 +1,155 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{Result, bail};
+use jcblocks::{
+    block::Block,
+    canvas::{Canvas, DEFAULT_CANVAS_HEIGHT, DEFAULT_CANVAS_WIDTH, PointStatus},
+};
+use rand::{SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+
+/// The longest block is five squares, anything smaller couldn't be dealt every shape.
+pub const MIN_BOARD_SIZE: usize = 5;
+pub const MAX_BOARD_SIZE: usize = 20;
+
+/// The size of the board games are played on, and how many of its squares are blocked.
+///
+/// Obstacles are squares that start occupied and stay that way: they count towards completing a
+/// line, but clearing the line leaves them where they were. They are scattered from the game's
+/// seed, so a seeded game always has them in the same places.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Board {
+    pub rows: usize,
+    pub columns: usize,
+    pub obstacles: usize,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Board {
+            rows: DEFAULT_CANVAS_HEIGHT,
+            columns: DEFAULT_CANVAS_WIDTH,
+            obstacles: 0,
+        }
+    }
+}
+
+impl Board {
+    /// The board every game used to be played on.
+    pub fn is_standard(&self) -> bool {
+        *self == Board::default()
+    }
+
+    /// Where the obstacles go in a game dealt from `seed`, as indexes into the canvas.
+    ///
+    /// No row or column is ever filled with obstacles alone, or it couldn't be cleared.
+    pub fn scatter(&self, seed: u64) -> Vec<usize> {
+        let mut squares: Vec<usize> = (0..self.rows * self.columns).collect();
+        squares.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+
+        let mut in_row = vec![0; self.rows];
+        let mut in_column = vec![0; self.columns];
+        let mut obstacles = Vec::new();
+        for i in squares {
+            if obstacles.len() == self.obstacles {
+                break;
+            }
+            let (row, column) = (i / self.columns, i % self.columns);
+            if in_row[row] + 1 < self.columns && in_column[column] + 1 < self.rows {
+                in_row[row] += 1;
+                in_column[column] += 1;
+                obstacles.push(i);
+            }
+        }
+
+        obstacles.sort_unstable();
+        obstacles
+    }
+
+    /// An empty board with `obstacles` in place.
+    pub fn canvas(&self, obstacles: &[usize]) -> Canvas {
+        let mut canvas = Canvas::new(self.rows, self.columns);
+        restore_obstacles(&mut canvas, obstacles);
+        canvas
+    }
+}
+
+/// Width by height, followed by the number of obstacles if there are any, e.g. `10x8+4`.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.columns, self.rows)?;
+        if self.obstacles > 0 {
+            write!(f, "+{}", self.obstacles)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Board {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (size, obstacles) = s.split_once('+').unwrap_or((s, "0"));
+        let Some((columns, rows)) = size.split_once('x') else {
+            bail!("Expected a board like 8x8 or 10x8+4, got '{}'", s);
+        };
+
+        Ok(Board {
+            rows: rows.parse()?,
+            columns: columns.parse()?,
+            obstacles: obstacles.parse()?,
+        })
+    }
+}
+
+/// Put back any obstacles a cleared line took with it.
+pub fn restore_obstacles(canvas: &mut Canvas, obstacles: &[usize]) {
+    // the canvas keeps its contents private, so fill it one square at a time.
+    let square = Block::rectangle(1, 1);
+    for &i in obstacles {
+        if let Some(PointStatus::Empty) = canvas.contents().get(i) {
+            let row = (i / canvas.columns) as i32;
+            let column = (i % canvas.columns) as i32;
+            if let Some(playable) = canvas.try_make_playable(&square, row, column) {
+                canvas.add(&playable);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn obstacles_are_scattered_from_the_seed() {
+        let board = Board {
+            rows: 5,
+            columns: 6,
+            obstacles: 12,
+        };
+        let obstacles = board.scatter(7);
+        assert_eq!(obstacles.len(), 12);
+        assert_eq!(board.scatter(7), obstacles);
+        assert_eq!(board.to_string(), "6x5+12");
+        assert_eq!("6x5+12".parse::<Board>().unwrap(), board);
+
+        let canvas = board.canvas(&obstacles);
+        for row in 0..board.rows {
+            assert_eq!(canvas.is_complete_row(row), Some(false));
+        }
+        for column in 0..board.columns {
+            assert_eq!(canvas.is_complete_column(column), Some(false));
+        }
+
+        let mut cleared = canvas.clone();
+        cleared.clear_all();
+        restore_obstacles(&mut cleared, &obstacles);
+        let occupied = |c: &Canvas| -> Vec<usize> {
+            (0..c.contents().len())
+                .filter(|&i| matches!(c.contents()[i], PointStatus::Occupied))
+                .collect()
+        };
+        assert_eq!(occupied(&cleared), obstacles);
+    }
+}
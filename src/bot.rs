@@ -0,0 +1,244 @@
+use jcblocks::{
+    block::Block,
+    canvas::{Canvas, PointStatus},
+    game::Game,
+};
+
+use crate::board::restore_obstacles;
+use crate::session::{Move, Session, positions};
+
+/// How many of the most promising partial plans are kept at each step of the search.
+const BEAM_WIDTH: usize = 256;
+
+/// Clearing a line is worth far more than a tidy board.
+const LINE_WEIGHT: i64 = 40;
+const EDGE_WEIGHT: i64 = 1;
+const HOLE_WEIGHT: i64 = 6;
+const REGION_WEIGHT: i64 = 3;
+
+/// The best way found to play the rest of the hand.
+#[derive(Debug, Clone)]
+pub struct Plan {
+    /// Moves in the order to make them. Each picks from the hand as it is at that point.
+    pub moves: Vec<Move>,
+    pub value: i64,
+}
+
+/// Search every order and position for the blocks left in the hand, returning the plan that
+/// leaves the best board.
+///
+/// Plans that place the whole hand always win over those that don't, since not being able to
+/// place a block is the end of the game. Returns `None` if nothing fits at all.
+pub fn plan(session: &Session) -> Option<Plan> {
+    if session.is_game_over() {
+        return None;
+    }
+
+    let start = Node {
+        game: copy_game(session.game()),
+        hand: session.hand().to_vec(),
+        moves: Vec::new(),
+        value: 0,
+    };
+
+    let mut beam = vec![start];
+    while beam.iter().any(|n| !n.hand.is_empty()) {
+        let mut next: Vec<Node> = beam
+            .iter()
+            .flat_map(|node| node.children(session.obstacles()))
+            .collect();
+        if next.is_empty() {
+            break;
+        }
+
+        next.sort_by_key(|n| std::cmp::Reverse(n.value));
+        next.truncate(BEAM_WIDTH);
+        beam = next;
+    }
+
+    beam.into_iter()
+        .filter(|n| !n.moves.is_empty())
+        .max_by_key(|n| n.value)
+        .map(|n| Plan {
+            moves: n.moves,
+            value: n.value,
+        })
+}
+
+/// Where the block at `selected` in the hand leaves the best board, looking no further ahead.
+pub fn best_move(session: &Session, selected: usize) -> Option<Move> {
+    let block = session.hand().get(selected)?;
+    positions(session.canvas(), block)
+        .into_iter()
+        .filter_map(|(row, column)| {
+            let mut game = copy_game(session.game());
+            game.maybe_place_block(block, row, column).ok()?;
+            restore_obstacles(&mut game.canvas, session.obstacles());
+            let m = Move {
+                selected,
+                row,
+                column,
+            };
+            Some((m, value(&game.canvas, game.score)))
+        })
+        .max_by_key(|(_, value)| *value)
+        .map(|(m, _)| m)
+}
+
+/// How good a board is after scoring `points`: lines cleared, less a penalty for fragmentation.
+pub fn value(canvas: &Canvas, points: usize) -> i64 {
+    points as i64 * LINE_WEIGHT - fragmentation(canvas)
+}
+
+/// How broken up the free space on the board is.
+///
+/// Counts the edges between free and occupied squares (the walls count as occupied), free
+/// squares boxed in on every side, and separate areas of free space. A board with a few large,
+/// smooth areas to play into scores low.
+pub fn fragmentation(canvas: &Canvas) -> i64 {
+    let rows = canvas.rows as i32;
+    let columns = canvas.columns as i32;
+    let is_free = |row: i32, column: i32| {
+        row >= 0
+            && column >= 0
+            && row < rows
+            && column < columns
+            && matches!(
+                canvas.contents()[(row * columns + column) as usize],
+                PointStatus::Empty
+            )
+    };
+
+    let mut edges = 0;
+    let mut holes = 0;
+    for row in 0..rows {
+        for column in 0..columns {
+            if !is_free(row, column) {
+                continue;
+            }
+
+            let blocked = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .filter(|(dy, dx)| !is_free(row + dy, column + dx))
+                .count();
+            edges += blocked as i64;
+            if blocked == 4 {
+                holes += 1;
+            }
+        }
+    }
+
+    // count the separate areas of free space with a flood fill.
+    let mut seen = vec![false; (rows * columns) as usize];
+    let mut regions = 0;
+    for start in 0..rows * columns {
+        if seen[start as usize] || !is_free(start / columns, start % columns) {
+            continue;
+        }
+
+        regions += 1;
+        let mut stack = vec![start];
+        seen[start as usize] = true;
+        while let Some(i) = stack.pop() {
+            let (row, column) = (i / columns, i % columns);
+            for (dy, dx) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (r, c) = (row + dy, column + dx);
+                if is_free(r, c) && !seen[(r * columns + c) as usize] {
+                    seen[(r * columns + c) as usize] = true;
+                    stack.push(r * columns + c);
+                }
+            }
+        }
+    }
+
+    edges * EDGE_WEIGHT + holes * HOLE_WEIGHT + regions * REGION_WEIGHT
+}
+
+/// A partial plan during the search.
+struct Node {
+    game: Game,
+    hand: Vec<Block>,
+    moves: Vec<Move>,
+    /// The `value` of the board, with the points scored over the whole game so plans can be
+    /// compared.
+    value: i64,
+}
+
+impl Node {
+    fn children(&self, obstacles: &[usize]) -> Vec<Node> {
+        let mut children = Vec::new();
+        for (selected, block) in self.hand.iter().enumerate() {
+            for (row, column) in positions(&self.game.canvas, block) {
+                let mut game = copy_game(&self.game);
+                if game.maybe_place_block(block, row, column).is_err() {
+                    continue;
+                }
+                restore_obstacles(&mut game.canvas, obstacles);
+
+                let mut hand = self.hand.clone();
+                hand.remove(selected);
+                let mut moves = self.moves.clone();
+                moves.push(Move {
+                    selected,
+                    row,
+                    column,
+                });
+
+                let value = value(&game.canvas, game.score);
+                children.push(Node {
+                    game,
+                    hand,
+                    moves,
+                    value,
+                });
+            }
+        }
+
+        children
+    }
+}
+
+fn copy_game(game: &Game) -> Game {
+    Game {
+        canvas: game.canvas.clone(),
+        score: game.score,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generator::BlockGenerator;
+
+    #[test]
+    fn fragmentation_prefers_tidy_boards() {
+        let mut tidy = Game::default();
+        tidy.maybe_place_block(&Block::rectangle(2, 2), 0, 0)
+            .unwrap();
+        let mut messy = Game::default();
+        messy
+            .maybe_place_block(&Block::rectangle(2, 2), 3, 3)
+            .unwrap();
+
+        assert!(fragmentation(&tidy.canvas) < fragmentation(&messy.canvas));
+    }
+
+    #[test]
+    fn plan_places_the_whole_hand_and_clears_lines() {
+        // a row with only the last square free, and a single square to fill it.
+        let mut game = Game::default();
+        game.maybe_place_block(&Block::line(5), 0, 0).unwrap();
+        game.maybe_place_block(&Block::line(2), 0, 5).unwrap();
+        let hand = vec![Block::tee(), Block::rectangle(1, 1)];
+        let session = Session::resume(game, hand, BlockGenerator::new(1), 2);
+
+        let plan = plan(&session).unwrap();
+        assert_eq!(plan.moves.len(), 2);
+
+        let mut played = session.clone();
+        for m in plan.moves.iter() {
+            played.apply(m).unwrap();
+        }
+        assert_eq!(played.score(), 50);
+    }
+}
@@ -0,0 +1,131 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+use crate::bench::Strategy;
+use crate::config::MAX_BLOCKS_PER_TURN;
+use crate::mode::GameMode;
+use crate::paths;
+use crate::theme::Theme;
+
+/// A block game for the terminal.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Path to the SQLite database holding scores and saved games. Defaults to `app.db` in
+    /// `$XDG_DATA_HOME/jcblocktui`, or `JCBLOCKTUI_DATA_DIR` if set.
+    #[arg(long, value_name = "PATH", global = true)]
+    pub db: Option<PathBuf>,
+
+    /// Configuration file to use instead of `config.toml` in `$XDG_CONFIG_HOME/jcblocktui`.
+    #[arg(long, value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Name to record high scores under. Defaults to `JCBLOCKTUI_NAME` or the login name.
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// Number of blocks dealt each turn.
+    #[arg(long, value_name = "N", value_parser = parse_blocks_per_turn)]
+    pub blocks_per_turn: Option<usize>,
+
+    /// Deal blocks from this seed, so the same moves always lead to the same game.
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Play today's daily challenge, which deals the same blocks to everyone.
+    #[arg(long, conflicts_with = "seed")]
+    pub daily: bool,
+
+    /// How the game ends: endless, blitz (against the clock), moves (a fixed number of
+    /// placements) or zen (never).
+    #[arg(long, value_parser = GameMode::NAMES, conflicts_with = "daily")]
+    pub mode: Option<String>,
+
+    /// Squares across the board.
+    #[arg(long, value_name = "N", conflicts_with = "daily")]
+    pub width: Option<usize>,
+
+    /// Squares from the top of the board to the bottom.
+    #[arg(long, value_name = "N", conflicts_with = "daily")]
+    pub height: Option<usize>,
+
+    /// Scatter this many squares that can't be cleared over the board.
+    #[arg(long, value_name = "N", conflicts_with = "daily")]
+    pub obstacles: Option<usize>,
+
+    /// Play without undo, so the score is never counted as assisted.
+    #[arg(long)]
+    pub no_undo: bool,
+
+    /// Start with the computer playing a demo game. Press any key to take over.
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Disable colors. Also enabled by setting `NO_COLOR`.
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Color theme.
+    #[arg(long, value_parser = Theme::NAMES)]
+    pub theme: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the high scores and exit.
+    Scores,
+    /// Watch a recorded game, the most recent one unless a file is given.
+    Replay {
+        /// A replay from the `replays` folder of the data directory.
+        file: Option<PathBuf>,
+    },
+    /// Pick a puzzle to solve from the built-in ones and any in the `puzzles` folder of the
+    /// config directory.
+    Puzzle {
+        /// A puzzle to play straight away, by file name or title.
+        name: Option<String>,
+    },
+    /// Play games with the computer to compare strategies, then print the results.
+    Bench {
+        /// A strategy to play, all of them unless given. Can be repeated.
+        #[arg(long = "strategy", value_name = "NAME", value_parser = Strategy::NAMES)]
+        strategies: Vec<String>,
+        /// Games to play with each strategy.
+        #[arg(long, default_value_t = 20)]
+        games: usize,
+        /// Seed of the first game, each game after uses the next one.
+        #[arg(long, default_value_t = 1)]
+        first_seed: u64,
+        /// Stop a game that gets this many hands in, counting it as a success.
+        #[arg(long, default_value_t = 100)]
+        max_turns: usize,
+        /// Print CSV instead of a table.
+        #[arg(long)]
+        csv: bool,
+    },
+}
+
+impl Cli {
+    /// The database given on the command line, or `app.db` in the data directory.
+    pub fn db_path(&self) -> Result<PathBuf> {
+        match &self.db {
+            Some(db) => Ok(db.clone()),
+            None => paths::default_db_path(),
+        }
+    }
+}
+
+fn parse_blocks_per_turn(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(n) if (1..=MAX_BLOCKS_PER_TURN).contains(&n) => Ok(n),
+        _ => Err(format!(
+            "expected a number from 1 to {}",
+            MAX_BLOCKS_PER_TURN
+        )),
+    }
+}
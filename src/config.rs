@@ -1,4 +1,482 @@
+use std::{collections::HashMap, path::Path, str::FromStr};
+
+use anyhow::{Context, Result, bail};
+use ratatui::{
+    style::{Color, Style},
+    text::Span,
+};
+use serde::Deserialize;
+
+use crate::board::{Board, MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use crate::cli::Cli;
+use crate::keymap::{Action, Keymap};
+use crate::mode::GameMode;
+use crate::theme::Theme;
+
 pub const EMPTY_BLOCK_REPRESENTATION: &str = "◌";
 pub const BLOCK_REPRESENTATION: &str = "●";
-pub const CONFLICT_REPRESENTATION: &str = "●";
+pub const CONFLICT_REPRESENTATION: &str = "◎";
+pub const OBSTACLE_REPRESENTATION: &str = "■";
+pub const HAND_BLOCK_REPRESENTATION: &str = "▅";
 pub const NUM_BLOCKS_PER_TURN: usize = 3;
+pub const MAX_BLOCKS_PER_TURN: usize = 5;
+pub const UNDO_HISTORY_SIZE: usize = 20;
+pub const SCOREBOARD_SIZE: usize = 5;
+pub const SCOREBOARD_ROWS_SHOWN: usize = 3;
+pub const BLITZ_MINUTES: u64 = 3;
+pub const MOVES_LIMIT: usize = 50;
+pub const DEMO_AFTER_SECONDS: u64 = 60;
+
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Settings loaded from `config.toml`, with command line options layered on top.
+///
+/// Every table and key is optional, anything left out keeps its default.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub player: PlayerConfig,
+    pub game: GameConfig,
+    pub board: BoardConfig,
+    pub glyphs: GlyphConfig,
+    pub colors: ColorConfig,
+    pub scoreboard: ScoreboardConfig,
+    pub layout: LayoutConfig,
+    /// Replacement keys for actions, e.g. `undo = ["u", "Ctrl+z"]`.
+    pub keys: HashMap<Action, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayerConfig {
+    /// Name to record high scores under.
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    /// Number of blocks dealt each turn.
+    pub blocks_per_turn: usize,
+    /// Number of placements that can be undone.
+    pub undo_history: usize,
+    /// Placements can be undone, which ranks the score with the other assisted scores.
+    pub allow_undo: bool,
+    /// Deal every game from this seed instead of a random one.
+    pub seed: Option<u64>,
+    /// Play the daily challenge instead of a normal game. Takes precedence over `seed`.
+    pub daily: bool,
+    /// How the game ends. The daily challenge is always endless.
+    pub mode: GameMode,
+    /// Length of a blitz game in minutes.
+    pub blitz_minutes: u64,
+    /// Number of placements in a moves game.
+    pub moves: usize,
+    /// Seconds without a key press before the demo starts playing, or 0 to never start it.
+    pub demo_after: u64,
+    /// Where the cursor goes when a different block is selected.
+    pub cursor: CursorPolicy,
+    /// Moving the cursor off one edge of the board brings it back on the opposite edge.
+    pub wrap_cursor: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            blocks_per_turn: NUM_BLOCKS_PER_TURN,
+            undo_history: UNDO_HISTORY_SIZE,
+            allow_undo: true,
+            seed: None,
+            daily: false,
+            mode: GameMode::default(),
+            blitz_minutes: BLITZ_MINUTES,
+            moves: MOVES_LIMIT,
+            demo_after: DEMO_AFTER_SECONDS,
+            cursor: CursorPolicy::default(),
+            wrap_cursor: false,
+        }
+    }
+}
+
+/// The size of the board, which the daily challenge ignores so everyone plays the same game.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BoardConfig {
+    /// Squares across.
+    pub width: usize,
+    /// Squares from top to bottom.
+    pub height: usize,
+    /// Squares scattered over the board that can't be cleared.
+    pub obstacles: usize,
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        let board = Board::default();
+        BoardConfig {
+            width: board.columns,
+            height: board.rows,
+            obstacles: board.obstacles,
+        }
+    }
+}
+
+/// Where the cursor goes when a different block is selected, including after a placement.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CursorPolicy {
+    /// Stay put, unless part of the block would be off the board, then go to the center.
+    Stay,
+    /// Stay as close as possible while keeping the whole block on the board.
+    #[default]
+    Clamp,
+    /// Always go back to the center of the board.
+    Center,
+    /// Go to wherever the block leaves the tidiest board.
+    BestFit,
+}
+
+/// The characters used to draw the board and the player's hand. Each must be one cell wide.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GlyphConfig {
+    pub empty: String,
+    pub block: String,
+    pub conflict: String,
+    pub obstacle: String,
+    pub hand: String,
+}
+
+impl Default for GlyphConfig {
+    fn default() -> Self {
+        GlyphConfig {
+            empty: EMPTY_BLOCK_REPRESENTATION.to_string(),
+            block: BLOCK_REPRESENTATION.to_string(),
+            conflict: CONFLICT_REPRESENTATION.to_string(),
+            obstacle: OBSTACLE_REPRESENTATION.to_string(),
+            hand: HAND_BLOCK_REPRESENTATION.to_string(),
+        }
+    }
+}
+
+/// A built-in theme, optionally with some of its colors replaced.
+///
+/// Colors are names (`red`, `lightblue`), indexes (`42`) or hex codes (`#ff8800`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorConfig {
+    pub theme: String,
+    pub no_color: bool,
+    pub occupied: Option<String>,
+    pub empty: Option<String>,
+    pub obstacle: Option<String>,
+    pub hovered: Option<String>,
+    pub conflict: Option<String>,
+    pub blast: Option<String>,
+    pub hint: Option<String>,
+    pub selected_block: Option<String>,
+    pub unplayable: Option<String>,
+    pub scoreboard: Option<String>,
+    pub key_hint: Option<String>,
+}
+
+impl Default for ColorConfig {
+    fn default() -> Self {
+        ColorConfig {
+            theme: "classic".to_string(),
+            no_color: false,
+            occupied: None,
+            empty: None,
+            obstacle: None,
+            hovered: None,
+            conflict: None,
+            blast: None,
+            hint: None,
+            selected_block: None,
+            unplayable: None,
+            scoreboard: None,
+            key_hint: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreboardConfig {
+    /// Number of high scores kept.
+    pub size: usize,
+    /// Number of high scores shown above the board.
+    pub shown: usize,
+}
+
+impl Default for ScoreboardConfig {
+    fn default() -> Self {
+        ScoreboardConfig {
+            size: SCOREBOARD_SIZE,
+            shown: SCOREBOARD_ROWS_SHOWN,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// Blank lines above and below the game, when there is room for them.
+    pub vertical_margin: u16,
+    /// Show the high scores above the board.
+    pub show_scoreboard: bool,
+    /// Show the key bindings along the bottom border.
+    pub show_help: bool,
+    /// Select and place blocks with the mouse. The terminal can't select text while it's on.
+    pub mouse: bool,
+    /// Animate lines being cleared.
+    pub animations: bool,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        LayoutConfig {
+            vertical_margin: 5,
+            show_scoreboard: true,
+            show_help: true,
+            mouse: true,
+            animations: true,
+        }
+    }
+}
+
+impl Config {
+    /// Load `config.toml` from the given path, or the config directory if there is none.
+    ///
+    /// A missing file in the config directory is not an error, everything just keeps its
+    /// default. Values are validated once command line options are applied by `with_overrides`.
+    pub fn load(path: Option<&Path>) -> Result<Config> {
+        match path {
+            Some(path) => Self::from_file(path),
+            None => {
+                let path = crate::paths::config_dir()?.join(CONFIG_FILE_NAME);
+                if path.exists() {
+                    Self::from_file(&path)
+                } else {
+                    Ok(Config::default())
+                }
+            }
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Config> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read config file {}", path.display()))?;
+
+        toml::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Let options given on the command line win over the config file.
+    pub fn with_overrides(mut self, cli: &Cli) -> Result<Config> {
+        if let Some(name) = &cli.name {
+            self.player.name = Some(name.clone());
+        }
+        if let Some(blocks_per_turn) = cli.blocks_per_turn {
+            self.game.blocks_per_turn = blocks_per_turn;
+        }
+        if let Some(seed) = cli.seed {
+            self.game.seed = Some(seed);
+        }
+        self.game.daily |= cli.daily;
+        self.game.allow_undo &= !cli.no_undo;
+        if let Some(mode) = cli.mode.as_deref().and_then(GameMode::by_name) {
+            self.game.mode = mode;
+        }
+        if let Some(width) = cli.width {
+            self.board.width = width;
+        }
+        if let Some(height) = cli.height {
+            self.board.height = height;
+        }
+        if let Some(obstacles) = cli.obstacles {
+            self.board.obstacles = obstacles;
+        }
+        if let Some(theme) = &cli.theme {
+            self.colors.theme = theme.clone();
+        }
+
+        // see https://no-color.org
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        self.colors.no_color |= cli.no_color || no_color_env;
+
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Check for values that would leave the game unplayable.
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=MAX_BLOCKS_PER_TURN).contains(&self.game.blocks_per_turn) {
+            bail!(
+                "game.blocks_per_turn must be from 1 to {}, got {}",
+                MAX_BLOCKS_PER_TURN,
+                self.game.blocks_per_turn
+            );
+        }
+        if self.game.blitz_minutes == 0 {
+            bail!("game.blitz_minutes must be at least 1");
+        }
+        if self.game.moves == 0 {
+            bail!("game.moves must be at least 1");
+        }
+        for (key, size) in [
+            ("board.width", self.board.width),
+            ("board.height", self.board.height),
+        ] {
+            if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size) {
+                bail!(
+                    "{} must be from {} to {}, got {}",
+                    key,
+                    MIN_BOARD_SIZE,
+                    MAX_BOARD_SIZE,
+                    size
+                );
+            }
+        }
+        // a board that is mostly obstacles leaves little room to play.
+        let max_obstacles = self.board.width * self.board.height / 4;
+        if self.board.obstacles > max_obstacles {
+            bail!(
+                "board.obstacles must be at most {} on a {}x{} board, got {}",
+                max_obstacles,
+                self.board.width,
+                self.board.height,
+                self.board.obstacles
+            );
+        }
+        if self.scoreboard.size == 0 {
+            bail!("scoreboard.size must be at least 1");
+        }
+
+        for (key, glyph) in [
+            ("glyphs.empty", &self.glyphs.empty),
+            ("glyphs.block", &self.glyphs.block),
+            ("glyphs.conflict", &self.glyphs.conflict),
+            ("glyphs.obstacle", &self.glyphs.obstacle),
+            ("glyphs.hand", &self.glyphs.hand),
+        ] {
+            if Span::raw(glyph.as_str()).width() != 1 {
+                bail!("{} must be a single character wide, got {:?}", key, glyph);
+            }
+        }
+
+        self.theme()?;
+        self.keymap()?;
+        Ok(())
+    }
+
+    /// The board described by the `[board]` table.
+    pub fn board(&self) -> Board {
+        Board {
+            rows: self.board.height,
+            columns: self.board.width,
+            obstacles: self.board.obstacles,
+        }
+    }
+
+    /// Build the key map described by the `[keys]` table.
+    pub fn keymap(&self) -> Result<Keymap> {
+        Keymap::new(&self.keys).context("Invalid [keys] table")
+    }
+
+    /// Build the theme described by the `[colors]` table.
+    pub fn theme(&self) -> Result<Theme> {
+        let colors = &self.colors;
+        if colors.no_color {
+            return Ok(Theme::mono());
+        }
+
+        let Some(mut theme) = Theme::by_name(&colors.theme) else {
+            bail!(
+                "colors.theme must be one of {}, got {:?}",
+                Theme::NAMES.join(", "),
+                colors.theme
+            );
+        };
+
+        for (key, color, style) in [
+            ("occupied", &colors.occupied, &mut theme.occupied),
+            ("empty", &colors.empty, &mut theme.empty),
+            ("obstacle", &colors.obstacle, &mut theme.obstacle),
+            ("hovered", &colors.hovered, &mut theme.hovered),
+            ("conflict", &colors.conflict, &mut theme.conflict),
+            ("blast", &colors.blast, &mut theme.blast),
+            ("hint", &colors.hint, &mut theme.hint),
+            (
+                "selected_block",
+                &colors.selected_block,
+                &mut theme.selected_block,
+            ),
+            ("unplayable", &colors.unplayable, &mut theme.unplayable),
+            ("scoreboard", &colors.scoreboard, &mut theme.scoreboard),
+            ("key_hint", &colors.key_hint, &mut theme.key_hint),
+        ] {
+            if let Some(color) = color {
+                let Ok(color) = Color::from_str(color) else {
+                    bail!("colors.{} is not a color: {:?}", key, color);
+                };
+                *style = style.patch(Style::new().fg(color));
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn partial_config_keeps_defaults() {
+        let config: Config = toml::from_str(
+            r##"
+            [game]
+            blocks_per_turn = 4
+            cursor = "best-fit"
+
+            [colors]
+            theme = "high-contrast"
+            blast = "#ff8800"
+        "##,
+        )
+        .unwrap();
+
+        config.validate().unwrap();
+        assert_eq!(config.game.blocks_per_turn, 4);
+        assert_eq!(config.game.undo_history, UNDO_HISTORY_SIZE);
+        assert!(config.game.allow_undo);
+        assert_eq!(config.game.cursor, CursorPolicy::BestFit);
+        assert_eq!(config.glyphs.block, BLOCK_REPRESENTATION);
+        assert_eq!(
+            config.theme().unwrap().blast.fg,
+            Some(Color::Rgb(0xff, 0x88, 0x00))
+        );
+    }
+
+    #[test]
+    fn invalid_config_is_rejected() {
+        for invalid in [
+            "[game]\nblocks_per_turn = 0",
+            "[glyphs]\nblock = \"##\"",
+            "[colors]\ntheme = \"nope\"",
+            "[colors]\noccupied = \"not a color\"",
+            "[scoreboard]\nsize = 0",
+            "[game]\nmode = \"blitz\"\nblitz_minutes = 0",
+            "[board]\nwidth = 4",
+            "[board]\nwidth = 5\nheight = 5\nobstacles = 7",
+            "[keys]\nquit = [\"Hyper+q\"]",
+        ] {
+            let config: Config = toml::from_str(invalid).unwrap();
+            assert!(config.validate().is_err(), "{} should be invalid", invalid);
+        }
+
+        assert!(toml::from_str::<Config>("[gmae]\nblocks_per_turn = 2").is_err());
+        assert!(toml::from_str::<Config>("[keys]\njump = [\"J\"]").is_err());
+        assert!(toml::from_str::<Config>("[game]\ncursor = \"wherever\"").is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::Result;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::scoreboard::HighScore;
+
+/// Today's date in UTC, which is what decides the daily challenge.
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+/// The seed everyone plays on `date`.
+///
+/// This must never change between versions, otherwise players on different versions would get
+/// different blocks. It is a 64-bit FNV-1a hash of the date.
+pub fn seed_for(date: NaiveDate) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    format!("jcblocktui-daily-{}", date.format("%Y-%m-%d"))
+        .bytes()
+        .fold(FNV_OFFSET_BASIS, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(FNV_PRIME)
+        })
+}
+
+/// Today's best scores and how one player is doing, as they were when read.
+#[derive(Debug, Clone, Default)]
+pub struct Standings {
+    pub leaderboard: Vec<HighScore>,
+    pub streak: u32,
+    /// The player's official attempt today, if they have had it.
+    pub attempt: Option<HighScore>,
+}
+
+/// The daily challenge for one date: one official attempt per player, with its own scoreboard.
+#[derive(Debug)]
+pub struct DailyChallenge {
+    date: NaiveDate,
+    db_conn: Connection,
+}
+
+impl DailyChallenge {
+    pub fn new<P>(connection_string: P, date: NaiveDate) -> Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let db_conn = Connection::open(connection_string)?;
+        db_conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS daily_scoreboard (
+                id INTEGER PRIMARY KEY,
+                date TEXT NOT NULL,
+                name TEXT NOT NULL,
+                score INTEGER NOT NULL,
+                "when" TEXT NOT NULL,
+                UNIQUE (date, name)
+            )"#,
+            (),
+        )?;
+
+        Ok(Self { date, db_conn })
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn seed(&self) -> u64 {
+        seed_for(self.date)
+    }
+
+    /// The official score `who` got today, if they have played.
+    pub fn attempt(&self, who: &str) -> Result<Option<HighScore>> {
+        Ok(self
+            .db_conn
+            .query_row(
+                r#"
+                SELECT name, score, "when" FROM daily_scoreboard
+                WHERE date = (?) AND name = (?)
+            "#,
+                params![date_key(self.date), who],
+                |row| self.high_score(row),
+            )
+            .optional()?)
+    }
+
+    /// Record the official attempt for `who`.
+    ///
+    /// Returns `Ok(false)` if they already had their attempt today, which is left as it was.
+    pub fn record(&self, who: &str, score: i64) -> Result<bool> {
+        let inserted = self.db_conn.execute(
+            r#"
+            INSERT OR IGNORE INTO daily_scoreboard (date, name, score, "when")
+            VALUES ((?), (?), (?), (?))
+        "#,
+            params![date_key(self.date), who, score, Utc::now().to_rfc3339()],
+        )?;
+
+        Ok(inserted == 1)
+    }
+
+    /// The best `n` official scores of the day.
+    pub fn leaderboard(&self, n: usize) -> Result<Vec<HighScore>> {
+        let mut statement = self.db_conn.prepare(
+            r#"
+            SELECT name, score, "when" FROM daily_scoreboard
+            WHERE date = (?)
+            ORDER BY score DESC LIMIT (?)
+        "#,
+        )?;
+
+        let found = statement
+            .query_map(params![date_key(self.date), n], |row| self.high_score(row))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(found)
+    }
+
+    /// The best `n` official scores of the day, along with the streak and attempt of `who` if we
+    /// know who they are.
+    pub fn standings(&self, n: usize, who: Option<&str>) -> Result<Standings> {
+        let mut standings = Standings {
+            leaderboard: self.leaderboard(n)?,
+            ..Standings::default()
+        };
+        if let Some(who) = who {
+            standings.streak = self.streak(who)?;
+            standings.attempt = self.attempt(who)?;
+        }
+
+        Ok(standings)
+    }
+
+    /// The number of consecutive days `who` has played, counting back from today.
+    ///
+    /// A streak survives until the end of the day after the last attempt, so it isn't broken
+    /// just because today's challenge hasn't been played yet.
+    pub fn streak(&self, who: &str) -> Result<u32> {
+        let mut statement = self.db_conn.prepare(
+            r#"
+            SELECT date FROM daily_scoreboard
+            WHERE name = (?) AND date <= (?)
+            ORDER BY date DESC
+        "#,
+        )?;
+        let dates = statement
+            .query_map(params![who, date_key(self.date)], |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let mut streak = 0;
+        let mut expected = self.date;
+        for date in dates {
+            let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") else {
+                break;
+            };
+
+            if streak == 0 && date < expected {
+                // today hasn't been played yet, the streak may still be going from yesterday.
+                expected = expected - Days::new(1);
+            }
+            if date != expected {
+                break;
+            }
+
+            streak += 1;
+            expected = expected - Days::new(1);
+        }
+
+        Ok(streak)
+    }
+
+    fn high_score(&self, row: &rusqlite::Row) -> rusqlite::Result<HighScore> {
+        let maybe_date: String = row.get(2)?;
+        let when = DateTime::parse_from_rfc3339(&maybe_date)
+            .map_err(|_| {
+                rusqlite::Error::InvalidColumnType(
+                    2,
+                    "when".to_string(),
+                    rusqlite::types::Type::Text,
+                )
+            })?
+            .with_timezone(&Utc);
+
+        Ok(HighScore::new(&row.get::<_, String>(0)?, row.get(1)?, when).with_seed(self.seed()))
+    }
+}
+
+fn date_key(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn seed_is_stable() {
+        assert_eq!(seed_for(date("2025-01-01")), seed_for(date("2025-01-01")));
+        assert_ne!(seed_for(date("2025-01-01")), seed_for(date("2025-01-02")));
+        assert_eq!(seed_for(date("2025-01-01")), 0x462b7b01a9ddafd1);
+    }
+
+    #[test]
+    fn one_attempt_per_day_and_streaks() {
+        let db_path =
+            std::env::temp_dir().join(format!("jcblocktui-daily-test-{}.db", std::process::id()));
+
+        for (day, score) in [("2025-03-01", 100), ("2025-03-03", 200), ("2025-03-04", 50)] {
+            let daily = DailyChallenge::new(&db_path, date(day)).unwrap();
+            assert!(daily.record("Allison", score).unwrap());
+            assert!(!daily.record("Allison", score + 1000).unwrap());
+        }
+
+        let daily = DailyChallenge::new(&db_path, date("2025-03-04")).unwrap();
+        assert_eq!(daily.attempt("Allison").unwrap().unwrap().score, 50);
+        assert!(daily.attempt("Bob").unwrap().is_none());
+        assert_eq!(daily.streak("Allison").unwrap(), 2);
+
+        // not played yet today, but the streak from yesterday still counts
+        let tomorrow = DailyChallenge::new(&db_path, date("2025-03-05")).unwrap();
+        assert_eq!(tomorrow.streak("Allison").unwrap(), 2);
+
+        let later = DailyChallenge::new(&db_path, date("2025-03-06")).unwrap();
+        assert_eq!(later.streak("Allison").unwrap(), 0);
+
+        let standings = daily.standings(3, Some("Allison")).unwrap();
+        assert_eq!(standings.leaderboard.len(), 1);
+        assert_eq!(standings.streak, 2);
+        assert_eq!(standings.attempt.unwrap().score, 50);
+
+        std::fs::remove_file(&db_path).unwrap();
+    }
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use rusqlite::Connection;
+
+/// Add `column` to `table` unless a previous version of the schema already has it.
+///
+/// `definition` is everything after the column name, e.g. `INTEGER NOT NULL DEFAULT 0`.
+pub(crate) fn add_column_if_missing(
+    db_conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    let exists: bool = db_conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )?;
+
+    if !exists {
+        db_conn.execute(
+            &format!(
+                r#"ALTER TABLE {} ADD COLUMN "{}" {}"#,
+                table, column, definition
+            ),
+            (),
+        )?;
+    }
+
+    Ok(())
+}
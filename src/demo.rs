@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use crate::bot;
+use crate::session::{Move, Session};
+
+/// How long each step of the demo stays on screen.
+pub const STEP_DELAY: Duration = Duration::from_millis(400);
+
+/// Steps to linger on a finished game before starting another.
+const GAME_OVER_STEPS: u32 = 10;
+
+/// Plays games by itself, one visible step at a time: first showing the block where it is about
+/// to go, then placing it.
+#[derive(Debug, Default)]
+pub struct Demo {
+    aiming: Option<Move>,
+    game_over_for: u32,
+}
+
+impl Demo {
+    pub fn new() -> Self {
+        Demo::default()
+    }
+
+    /// The move about to be made, if any.
+    pub fn aiming(&self) -> Option<&Move> {
+        self.aiming.as_ref()
+    }
+
+    /// Aim the next move, make the one aimed last time, or once a finished game has been shown
+    /// for a while start a new one with `restart`.
+    pub fn step(&mut self, session: &mut Session, restart: impl FnOnce() -> Session) {
+        if session.is_game_over() {
+            self.game_over_for += 1;
+            if self.game_over_for >= GAME_OVER_STEPS {
+                *session = restart();
+                self.game_over_for = 0;
+            }
+            return;
+        }
+
+        match self.aiming.take() {
+            Some(m) => {
+                let _ = session.apply(&m);
+            }
+            None => self.aiming = bot::plan(session).and_then(|plan| plan.moves.first().copied()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::generator::BlockGenerator;
+
+    #[test]
+    fn demo_aims_places_and_starts_over() {
+        let mut session = Session::new(BlockGenerator::new(9), 3);
+        let mut demo = Demo::new();
+        let restart = || Session::new(BlockGenerator::new(10), 3);
+
+        demo.step(&mut session, restart);
+        assert!(demo.aiming().is_some());
+        assert_eq!(session.hand().len(), 3);
+
+        demo.step(&mut session, restart);
+        assert!(demo.aiming().is_none());
+        assert_eq!(session.hand().len(), 2);
+
+        session.resign();
+        for _ in 0..GAME_OVER_STEPS {
+            demo.step(&mut session, restart);
+        }
+        assert!(!session.is_game_over());
+        assert_eq!(session.seed(), 10);
+    }
+}
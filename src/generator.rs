@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use jcblocks::{block::Block, canvas::Canvas};
+use rand::{SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+
+use crate::shapes;
+
+/// Deals blocks from a seeded random number generator, so the same seed and the same placements
+/// always produce the same hands.
+///
+/// This follows the same rules as `Game::generate_blocks`: every hand is guaranteed to fit on
+/// the canvas it was generated for. Puzzles deal a fixed sequence instead, see `sequence`.
+#[derive(Debug, Clone)]
+pub struct BlockGenerator {
+    seed: u64,
+    rng: ChaCha8Rng,
+    /// Blocks still to be dealt, in order, in place of random ones.
+    sequence: Option<VecDeque<Block>>,
+}
+
+impl BlockGenerator {
+    pub fn new(seed: u64) -> Self {
+        BlockGenerator {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            sequence: None,
+        }
+    }
+
+    /// Deal `blocks` in order, whether they fit or not, and nothing once they run out.
+    pub fn sequence(blocks: Vec<Block>) -> Self {
+        BlockGenerator {
+            sequence: Some(blocks.into()),
+            ..BlockGenerator::new(0)
+        }
+    }
+
+    /// Construct a generator with a random seed.
+    pub fn random() -> Self {
+        BlockGenerator::new(rand::random())
+    }
+
+    /// Resume a generator that has already dealt some blocks, see `position`.
+    pub fn resume(seed: u64, position: u128) -> Self {
+        let mut generator = BlockGenerator::new(seed);
+        generator.rng.set_word_pos(position);
+        generator
+    }
+
+    /// The seed this generator started from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// How far through its random sequence the generator is.
+    pub fn position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+
+    /// Generate `n` blocks that are guaranteed to fit within the available playing area.
+    ///
+    /// A fixed sequence deals up to `n` of its remaining blocks instead, or `None` once it is
+    /// used up.
+    pub fn generate(&mut self, canvas: &Canvas, n: usize) -> Option<Vec<Block>> {
+        if let Some(sequence) = self.sequence.as_mut() {
+            let n = n.min(sequence.len());
+            return (n > 0).then(|| sequence.drain(..n).collect());
+        }
+
+        let mut blocks = Vec::new();
+        let mut shadow_canvas = canvas.clone();
+        for _ in 0..n {
+            // no more blocks could fit!
+            blocks.push(self.generate_one(&mut shadow_canvas)?);
+        }
+
+        blocks.reverse();
+        Some(blocks)
+    }
+
+    fn generate_one(&mut self, canvas: &mut Canvas) -> Option<Block> {
+        let mut all_blocks = shapes::catalogue();
+        all_blocks.shuffle(&mut self.rng);
+        for block in &mut all_blocks {
+            for _ in (0..360).step_by(90) {
+                if let Some(playable) = canvas.can_fit(block) {
+                    canvas.add(&playable);
+                    return Some(block.to_owned());
+                }
+                block.rotate_left();
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn deal(generator: &mut BlockGenerator) -> Vec<Vec<(i32, i32)>> {
+        generator
+            .generate(&Canvas::default(), 3)
+            .unwrap()
+            .iter()
+            .map(|b| b.coordinates().iter().map(|p| (p.x, p.y)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_blocks() {
+        let mut a = BlockGenerator::new(42);
+        let mut b = BlockGenerator::new(42);
+        for _ in 0..10 {
+            assert_eq!(deal(&mut a), deal(&mut b));
+        }
+    }
+
+    #[test]
+    fn resume_where_we_left_off() {
+        let mut original = BlockGenerator::new(7);
+        deal(&mut original);
+
+        let mut resumed = BlockGenerator::resume(original.seed(), original.position());
+        assert_eq!(deal(&mut original), deal(&mut resumed));
+    }
+}
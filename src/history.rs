@@ -0,0 +1,126 @@
+use std::collections::VecDeque;
+
+use crate::block_index::BlockIndex;
+use crate::scoring::Scorer;
+use crate::session::Session;
+
+/// Everything needed to put the game back the way it was before a placement.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub session: Session,
+    pub selected: BlockIndex,
+    pub scorer: Scorer,
+    /// Placements made so far.
+    pub moves: usize,
+}
+
+/// A bounded undo/redo stack of game snapshots.
+#[derive(Debug)]
+pub struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    capacity: usize,
+}
+
+impl History {
+    /// Construct a history that remembers at most `capacity` snapshots.
+    pub fn new(capacity: usize) -> Self {
+        History {
+            undo: VecDeque::with_capacity(capacity),
+            redo: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Remember the state from before a placement, forgetting the oldest snapshot when full.
+    ///
+    /// Recording a new snapshot discards anything that could have been redone.
+    pub fn record(&mut self, snapshot: Snapshot) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+        self.redo.clear();
+    }
+
+    /// Step back, returning the snapshot to restore. `current` becomes available to redo.
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+
+    /// Step forward again, returning the snapshot to restore. `current` becomes available to undo.
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        Some(next)
+    }
+
+    /// Forget everything.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use jcblocks::{block::Block, game::Game};
+
+    use super::*;
+    use crate::generator::BlockGenerator;
+
+    fn snapshot(score: usize) -> Snapshot {
+        Snapshot {
+            session: Session::resume(
+                Game {
+                    score,
+                    ..Game::default()
+                },
+                vec![Block::tee()],
+                BlockGenerator::new(0),
+                3,
+            ),
+            selected: BlockIndex::default(),
+            scorer: Scorer::default(),
+            moves: 0,
+        }
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut history = History::new(5);
+        history.record(snapshot(0));
+        history.record(snapshot(50));
+
+        assert_eq!(history.undo(snapshot(100)).unwrap().session.score(), 50);
+        assert_eq!(history.undo(snapshot(50)).unwrap().session.score(), 0);
+        assert!(history.undo(snapshot(0)).is_none());
+
+        assert_eq!(history.redo(snapshot(0)).unwrap().session.score(), 50);
+        assert_eq!(history.redo(snapshot(50)).unwrap().session.score(), 100);
+        assert!(history.redo(snapshot(100)).is_none());
+
+        // a fresh placement invalidates the redo stack
+        history.undo(snapshot(100));
+        history.record(snapshot(50));
+        assert!(history.redo(snapshot(75)).is_none());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut history = History::new(2);
+        for score in 0..5 {
+            history.record(snapshot(score));
+        }
+
+        assert_eq!(history.undo(snapshot(5)).unwrap().session.score(), 4);
+        assert_eq!(history.undo(snapshot(4)).unwrap().session.score(), 3);
+        assert!(history.undo(snapshot(3)).is_none());
+    }
+}
@@ -0,0 +1,392 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use anyhow::{Result, bail};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Everything the player can do during a game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveLeft,
+    MoveDown,
+    MoveUp,
+    MoveRight,
+    JumpLeft,
+    JumpDown,
+    JumpUp,
+    JumpRight,
+    CycleNext,
+    CyclePrevious,
+    Place,
+    Undo,
+    Redo,
+    Hint,
+    NewGame,
+    /// Carry on with the saved game rather than starting a new one.
+    Continue,
+    Quit,
+}
+
+impl Action {
+    /// All actions, in the order they are listed in help text.
+    pub const ALL: [Action; 17] = [
+        Action::Quit,
+        Action::MoveLeft,
+        Action::MoveDown,
+        Action::MoveUp,
+        Action::MoveRight,
+        Action::JumpLeft,
+        Action::JumpDown,
+        Action::JumpUp,
+        Action::JumpRight,
+        Action::CycleNext,
+        Action::CyclePrevious,
+        Action::Place,
+        Action::Undo,
+        Action::Redo,
+        Action::Hint,
+        Action::NewGame,
+        Action::Continue,
+    ];
+
+    fn default_keys(&self) -> &'static [&'static str] {
+        match self {
+            Action::MoveLeft => &["h", "Left"],
+            Action::MoveDown => &["j", "Down"],
+            Action::MoveUp => &["k", "Up"],
+            Action::MoveRight => &["l", "Right"],
+            Action::JumpLeft => &["H", "Home"],
+            Action::JumpDown => &["G", "PageDown"],
+            Action::JumpUp => &["g g", "PageUp"],
+            Action::JumpRight => &["L", "End"],
+            Action::CycleNext => &["n"],
+            Action::CyclePrevious => &["N"],
+            Action::Place => &["Space"],
+            Action::Undo => &["u"],
+            Action::Redo => &["r"],
+            Action::Hint => &["?"],
+            Action::NewGame => &["Enter"],
+            Action::Continue => &["c"],
+            Action::Quit => &["q"],
+        }
+    }
+}
+
+/// A key plus any modifiers held with it, e.g. `Ctrl+r`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // shifted characters arrive already upper-cased, so shift is implied by the character.
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => KeyBinding {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            _ => KeyBinding { code, modifiers },
+        }
+    }
+
+    /// Returns true if the key event is this binding.
+    pub fn matches(&self, key_event: &KeyEvent) -> bool {
+        *self == KeyBinding::new(key_event.code, key_event.modifiers)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = anyhow::Error;
+
+    /// Parse a key name such as `h`, `H`, `Space`, `Left`, `F5` or `Ctrl+Alt+z`.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = s.split('+').collect();
+        let key = match parts.pop() {
+            // `+` on its own, or at the end of a combination like `Ctrl++`
+            Some("") if s.ends_with('+') => {
+                parts.pop();
+                "+"
+            }
+            Some(key) => key,
+            None => bail!("Empty key binding"),
+        };
+
+        for modifier in parts {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("Unknown modifier {:?} in key binding {:?}", modifier, s),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                f if f.starts_with('f') && f[1..].parse::<u8>().is_ok() => {
+                    KeyCode::F(f[1..].parse()?)
+                }
+                _ => bail!("Unknown key {:?} in key binding {:?}", key, s),
+            },
+        };
+
+        Ok(KeyBinding::new(code, modifiers))
+    }
+}
+
+impl Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::PageUp => write!(f, "PageUp"),
+            KeyCode::PageDown => write!(f, "PageDown"),
+            KeyCode::BackTab => write!(f, "BackTab"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Keys pressed one after the other, written with spaces between them, e.g. `g g`. Most are
+/// just one key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence(Vec<KeyBinding>);
+
+impl KeySequence {
+    /// Returns true if the key events are this sequence, or the start of it.
+    fn starts_with(&self, key_events: &[KeyEvent]) -> bool {
+        key_events.len() <= self.0.len() && self.0.iter().zip(key_events).all(|(k, e)| k.matches(e))
+    }
+
+    /// Returns true if this sequence begins with all of `other`, including being the same.
+    fn overlaps(&self, other: &KeySequence) -> bool {
+        let shorter = self.0.len().min(other.0.len());
+        self.0[..shorter] == other.0[..shorter]
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // a space on its own is the space bar, not a separator.
+        if s.trim().is_empty() {
+            return Ok(KeySequence(vec![s.parse()?]));
+        }
+
+        Ok(KeySequence(
+            s.split(' ')
+                .filter(|k| !k.is_empty())
+                .map(|k| k.parse())
+                .collect::<Result<Vec<_>>>()?,
+        ))
+    }
+}
+
+impl Display for KeySequence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(|k| k.to_string()).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+/// What the keys pressed so far add up to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMatch {
+    Action(Action),
+    /// The start of a longer sequence, wait for the next key.
+    Pending,
+    Unbound,
+}
+
+/// Maps keys to actions. Every action may have several keys.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeySequence>>,
+}
+
+impl Keymap {
+    /// Build a key map from the defaults, replacing the keys of any action found in `overrides`.
+    ///
+    /// Fails if a key name can't be parsed or the same key ends up bound to two actions,
+    /// including one action's keys being the start of another's.
+    pub fn new(overrides: &HashMap<Action, Vec<String>>) -> Result<Self> {
+        let mut bindings = HashMap::new();
+        for action in Action::ALL {
+            let keys = match overrides.get(&action) {
+                Some(keys) => keys.iter().map(|k| k.parse()).collect::<Result<Vec<_>>>()?,
+                None => action
+                    .default_keys()
+                    .iter()
+                    .map(|k| k.parse())
+                    .collect::<Result<Vec<_>>>()?,
+            };
+            bindings.insert(action, keys);
+        }
+
+        let keymap = Keymap { bindings };
+        for (i, a) in Action::ALL.iter().enumerate() {
+            for b in Action::ALL.iter().skip(i + 1) {
+                for key in keymap.keys(*a) {
+                    if keymap.keys(*b).iter().any(|k| k.overlaps(key)) {
+                        bail!("Key {} is bound to both {:?} and {:?}", key, a, b);
+                    }
+                }
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    /// The action bound to the key event on its own, if any.
+    pub fn action(&self, key_event: &KeyEvent) -> Option<Action> {
+        match self.resolve(&[*key_event]) {
+            KeyMatch::Action(action) => Some(action),
+            _ => None,
+        }
+    }
+
+    /// Look up the keys pressed since the last action.
+    pub fn resolve(&self, key_events: &[KeyEvent]) -> KeyMatch {
+        let mut result = KeyMatch::Unbound;
+        for action in Action::ALL {
+            for keys in self.keys(action) {
+                if !keys.starts_with(key_events) {
+                    continue;
+                }
+                if keys.0.len() == key_events.len() {
+                    return KeyMatch::Action(action);
+                }
+                result = KeyMatch::Pending;
+            }
+        }
+
+        result
+    }
+
+    /// All keys bound to `action`, the first being the one shown in help text.
+    pub fn keys(&self, action: Action) -> &[KeySequence] {
+        self.bindings
+            .get(&action)
+            .map(|keys| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The keys shown for a group of actions, e.g. `h,j,k,l` for movement.
+    pub fn describe(&self, actions: &[Action]) -> String {
+        actions
+            .iter()
+            .filter_map(|a| self.keys(*a).first())
+            .map(|k| k.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::new(&HashMap::new()).expect("Default key bindings are valid.")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_and_display() {
+        for name in [
+            "h",
+            "H",
+            "Space",
+            "Left",
+            "Enter",
+            "F5",
+            "Ctrl+r",
+            "Ctrl+Alt+z",
+            "+",
+        ] {
+            let binding: KeyBinding = name.parse().unwrap();
+            assert_eq!(binding.to_string(), name);
+        }
+
+        assert_eq!("Shift+h".parse::<KeyBinding>().unwrap().to_string(), "H");
+        assert!("Hyper+h".parse::<KeyBinding>().is_err());
+        assert!("Nope".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn remapped_keys() {
+        let overrides = HashMap::from([
+            (Action::Undo, vec!["Ctrl+z".to_string(), "u".to_string()]),
+            (Action::Redo, vec!["Ctrl+y".to_string()]),
+        ]);
+        let keymap = Keymap::new(&overrides).unwrap();
+
+        let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        let z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::NONE);
+        let r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE);
+        let shift_h = KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT);
+        assert_eq!(keymap.action(&ctrl_z), Some(Action::Undo));
+        assert_eq!(keymap.action(&z), None);
+        assert_eq!(keymap.action(&r), None);
+        assert_eq!(keymap.action(&shift_h), Some(Action::JumpLeft));
+        assert_eq!(
+            keymap.describe(&[Action::Undo, Action::Redo]),
+            "Ctrl+z,Ctrl+y"
+        );
+
+        let clash = HashMap::from([(Action::Quit, vec!["n".to_string()])]);
+        assert!(Keymap::new(&clash).is_err());
+        let prefix = HashMap::from([(Action::Quit, vec!["g".to_string()])]);
+        assert!(Keymap::new(&prefix).is_err());
+    }
+
+    #[test]
+    fn key_sequences() {
+        let keymap = Keymap::default();
+        let g = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::NONE);
+        let h = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE);
+        assert_eq!(keymap.resolve(&[g]), KeyMatch::Pending);
+        assert_eq!(keymap.resolve(&[g, g]), KeyMatch::Action(Action::JumpUp));
+        assert_eq!(keymap.resolve(&[g, h]), KeyMatch::Unbound);
+        assert_eq!(keymap.action(&g), None);
+        assert_eq!(keymap.describe(&[Action::JumpUp]), "g g");
+        assert_eq!(
+            " ".parse::<KeySequence>().unwrap(),
+            "Space".parse().unwrap()
+        );
+    }
+}
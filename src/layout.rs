@@ -0,0 +1,273 @@
+use ratatui::layout::{Constraint, Flex, Layout, Margin, Rect};
+
+/// Columns taken up by each block in the hand, wide enough for a line of five.
+pub const SLOT_WIDTH: u16 = 12;
+/// Lines taken up by the tallest block in the hand.
+pub const HAND_HEIGHT: u16 = 5;
+/// Squares of the board are drawn this many times their smallest size at most.
+const MAX_SCALE: u16 = 3;
+/// The narrowest the column beside the board can be in the wide layout.
+const PANEL_WIDTH: u16 = 34;
+/// Columns between the board and the panel beside it.
+const PANEL_GAP: u16 = 4;
+/// Lines for warnings and the game over message.
+const WARNING_HEIGHT: u16 = 2;
+/// The game over message wraps onto more lines in the narrow column beside the board.
+const WIDE_WARNING_HEIGHT: u16 = 4;
+/// Lines for messages below the hand.
+const MESSAGES_HEIGHT: u16 = 2;
+
+/// What has to fit on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contents {
+    pub rows: u16,
+    pub columns: u16,
+    /// Blocks in a full hand.
+    pub hand: u16,
+    /// Lines taken up by each scoreboard, 0 if they're hidden.
+    pub scoreboard: u16,
+    /// Blank lines wanted above and below everything, when there is room for them.
+    pub margin: u16,
+}
+
+impl Contents {
+    /// The smallest terminal the game can be drawn in.
+    pub fn minimum_size(&self) -> (u16, u16) {
+        // whichever needs less room overall.
+        let area = |(width, height): (u16, u16)| u32::from(width) * u32::from(height);
+        let (stacked, wide) = (self.stacked_size(1), self.wide_size(1));
+        match area(wide) < area(stacked) {
+            true => wide,
+            false => stacked,
+        }
+    }
+
+    fn board_size(&self, scale: u16) -> (u16, u16) {
+        (self.columns * 2 * scale, self.rows * scale)
+    }
+
+    fn hand_width(&self) -> u16 {
+        self.hand * SLOT_WIDTH
+    }
+
+    /// Everything one above the other, inside the border.
+    fn stacked_size(&self, scale: u16) -> (u16, u16) {
+        let (board_width, board_height) = self.board_size(scale);
+        (
+            board_width.max(self.hand_width()) + 4,
+            self.scoreboard + WARNING_HEIGHT + board_height + 1 + HAND_HEIGHT + MESSAGES_HEIGHT + 2,
+        )
+    }
+
+    /// The board with everything else in a column beside it.
+    fn wide_size(&self, scale: u16) -> (u16, u16) {
+        let (board_width, board_height) = self.board_size(scale);
+        (
+            board_width + PANEL_GAP + self.panel_width() + 4,
+            board_height.max(self.panel_height()) + 2,
+        )
+    }
+
+    fn panel_width(&self) -> u16 {
+        self.hand_width().max(PANEL_WIDTH)
+    }
+
+    fn panel_height(&self) -> u16 {
+        2 * self.scoreboard + WIDE_WARNING_HEIGHT + 1 + HAND_HEIGHT + MESSAGES_HEIGHT
+    }
+
+    /// The largest scale the board can be drawn at with everything else around it, if any.
+    fn best_scale(&self, area: Rect, size: impl Fn(u16) -> (u16, u16)) -> Option<u16> {
+        (1..=MAX_SCALE).rev().find(|&scale| {
+            let (width, height) = size(scale);
+            width <= area.width && height <= area.height
+        })
+    }
+}
+
+/// Where everything goes on the screen, worked out from the size of the terminal.
+///
+/// The board is drawn as big as it can be. Everything usually goes one above the other, but a
+/// terminal much wider than it is tall gets the board with a column of everything else beside
+/// it whenever that lets the board be drawn bigger.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenLayout {
+    /// Where the two scoreboards go.
+    pub scoreboards: [Rect; 2],
+    /// Warnings and the game over message.
+    pub warning: Rect,
+    /// Exactly the size of the board.
+    pub board: Rect,
+    pub hand: Rect,
+    /// Messages about the replay, demo, puzzle or how to play again.
+    pub messages: Rect,
+    /// Columns and lines taken up by each square of the board.
+    pub cell_width: u16,
+    pub cell_height: u16,
+    /// The board has everything else beside it rather than below.
+    pub wide: bool,
+}
+
+impl ScreenLayout {
+    /// Lay out `contents` in `area`, the whole terminal, or `None` if it is too small.
+    pub fn new(area: Rect, contents: &Contents) -> Option<ScreenLayout> {
+        let stacked = contents.best_scale(area, |scale| contents.stacked_size(scale));
+        let wide = contents.best_scale(area, |scale| contents.wide_size(scale));
+        match (stacked, wide) {
+            (Some(stacked), Some(wide)) if wide > stacked => {
+                Some(ScreenLayout::wide(area, contents, wide))
+            }
+            (Some(stacked), _) => Some(ScreenLayout::stacked(area, contents, stacked)),
+            (None, Some(wide)) => Some(ScreenLayout::wide(area, contents, wide)),
+            (None, None) => None,
+        }
+    }
+
+    fn stacked(area: Rect, contents: &Contents, scale: u16) -> ScreenLayout {
+        let (board_width, board_height) = contents.board_size(scale);
+        let (_, needed) = contents.stacked_size(scale);
+        let inner = area.inner(Margin::new(1, 1));
+
+        // any height left over goes to the margin first, which counts the border, then is shared
+        // between the gaps either side of the board and the messages.
+        let spare = area.height - needed;
+        let margin = contents.margin.saturating_sub(1).min(spare / 2);
+        let spare = spare - 2 * margin;
+        let [scoreboards, warning, board, _, hand, messages] = Layout::vertical([
+            Constraint::Length(contents.scoreboard),
+            Constraint::Length(WARNING_HEIGHT + spare / 4),
+            Constraint::Length(board_height),
+            Constraint::Length(1 + spare / 4),
+            Constraint::Length(HAND_HEIGHT),
+            Constraint::Fill(1),
+        ])
+        .vertical_margin(margin)
+        .areas(inner);
+
+        let [local, assisted, _] = Layout::horizontal([Constraint::Ratio(1, 3); 3])
+            .horizontal_margin(4)
+            .areas(scoreboards);
+
+        ScreenLayout {
+            scoreboards: [local, assisted],
+            warning,
+            board: centered(board, board_width, board_height),
+            hand,
+            messages,
+            cell_width: 2 * scale,
+            cell_height: scale,
+            wide: false,
+        }
+    }
+
+    fn wide(area: Rect, contents: &Contents, scale: u16) -> ScreenLayout {
+        let (board_width, board_height) = contents.board_size(scale);
+        let inner = area.inner(Margin::new(1, 1));
+        let [board_column, panel] = Layout::horizontal([
+            Constraint::Length(board_width),
+            Constraint::Length(contents.panel_width()),
+        ])
+        .spacing(PANEL_GAP)
+        .flex(Flex::Center)
+        .areas(inner);
+
+        let [local, assisted, warning, _, hand, messages] = Layout::vertical([
+            Constraint::Length(contents.scoreboard),
+            Constraint::Length(contents.scoreboard),
+            Constraint::Length(WIDE_WARNING_HEIGHT),
+            Constraint::Length(1),
+            Constraint::Length(HAND_HEIGHT),
+            Constraint::Length(MESSAGES_HEIGHT),
+        ])
+        .flex(Flex::Center)
+        .areas(panel);
+
+        ScreenLayout {
+            scoreboards: [local, assisted],
+            warning,
+            board: centered(board_column, board_width, board_height),
+            hand,
+            messages,
+            cell_width: 2 * scale,
+            cell_height: scale,
+            wide: true,
+        }
+    }
+
+    /// Where each square of a board with `rows` and `columns` is drawn, in the same order as
+    /// the canvas. Row 0 is at the bottom.
+    pub fn board_cells(&self, rows: u16, columns: u16) -> Vec<Rect> {
+        let mut cells = Vec::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                cells.push(Rect::new(
+                    self.board.x + column * self.cell_width,
+                    self.board.y + (rows - 1 - row) * self.cell_height,
+                    self.cell_width,
+                    self.cell_height,
+                ));
+            }
+        }
+
+        cells
+    }
+
+    /// Where each block of a full hand of `blocks` is drawn.
+    pub fn hand_slots(&self, blocks: u16) -> Vec<Rect> {
+        Layout::horizontal(vec![Constraint::Length(SLOT_WIDTH); blocks as usize])
+            .flex(Flex::Center)
+            .split(self.hand)
+            .to_vec()
+    }
+}
+
+/// A `width` by `height` area in the middle of `area`.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn board_grows_and_moves_aside_to_fit() {
+        let contents = Contents {
+            rows: 8,
+            columns: 8,
+            hand: 3,
+            scoreboard: 4,
+            margin: 5,
+        };
+
+        let stacked = ScreenLayout::new(Rect::new(0, 0, 100, 40), &contents).unwrap();
+        assert!(!stacked.wide);
+        assert_eq!((stacked.cell_width, stacked.cell_height), (6, 3));
+        assert_eq!((stacked.board.width, stacked.board.height), (48, 24));
+
+        // too short to stack a bigger board, but wide enough to put everything beside it.
+        let wide = ScreenLayout::new(Rect::new(0, 0, 120, 30), &contents).unwrap();
+        assert!(wide.wide);
+        assert_eq!(wide.cell_height, 3);
+        assert!(!wide.board.intersects(wide.hand));
+
+        let cells = wide.board_cells(8, 8);
+        assert_eq!(cells.len(), 64);
+        assert_eq!(cells[0].y, wide.board.bottom() - 3);
+        assert!(
+            cells
+                .iter()
+                .all(|cell| wide.board.contains(cell.as_position()))
+        );
+
+        let (width, height) = contents.minimum_size();
+        assert!(ScreenLayout::new(Rect::new(0, 0, width, height), &contents).is_some());
+        assert!(ScreenLayout::new(Rect::new(0, 0, width - 1, height - 1), &contents).is_none());
+    }
+}
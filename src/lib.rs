@@ -1,4 +1,27 @@
+pub mod animation;
 pub mod app;
+pub mod bench;
 pub mod block_index;
+pub mod board;
+pub mod bot;
+pub mod cli;
 pub mod config;
+pub mod daily;
+mod db;
+pub mod demo;
+pub mod generator;
+pub mod history;
+pub mod keymap;
+pub mod layout;
+pub mod mode;
+pub mod paths;
+pub mod player;
+pub mod puzzle;
+pub mod puzzle_menu;
+pub mod replay;
+pub mod save;
 pub mod scoreboard;
+pub mod scoring;
+pub mod session;
+pub mod shapes;
+pub mod theme;
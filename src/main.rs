@@ -1,9 +1,220 @@
-use anyhow::Result;
-use jcblocktui::app::App;
+use std::io::stdout;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use clap::Parser;
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+};
+use jcblocktui::{
+    app::App,
+    bench::{self, Strategy, Summary},
+    cli::{Cli, Command},
+    config::Config,
+    daily::{self, DailyChallenge},
+    mode::GameMode,
+    paths, player,
+    puzzle::{self, PuzzleBook},
+    puzzle_menu::PuzzleMenu,
+    replay::Replay,
+    scoreboard::{HighScore, LocalScoreBoard, Scoreboard},
+};
 
 fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    // set everything up before taking over the terminal so errors are readable.
+    let config = Config::load(cli.config.as_deref())?.with_overrides(&cli)?;
+    let db_path = cli.db_path()?;
+
+    match cli.command {
+        Some(Command::Scores) => print_scores(&config, &db_path),
+        Some(Command::Bench {
+            strategies,
+            games,
+            first_seed,
+            max_turns,
+            csv,
+        }) => run_bench(&config, &strategies, games, first_seed, max_turns, csv),
+        Some(Command::Replay { file }) => {
+            let path = match file {
+                Some(file) => file,
+                None => match Replay::latest(&paths::replay_dir()?)? {
+                    Some(latest) => latest,
+                    None => bail!("There are no replays yet, finish a game first"),
+                },
+            };
+            let replay = Replay::load(&path)?;
+            play(App::replay(&config, &db_path, &replay)?, false)
+        }
+        Some(Command::Puzzle { name }) => play_puzzles(&config, &db_path, name.as_deref()),
+        None => {
+            let mut app = App::new(&config, &db_path)?;
+            if cli.demo {
+                app.start_demo();
+            }
+            play(app, config.layout.mouse)
+        }
+    }
+}
+
+fn play(app: App, mouse: bool) -> Result<()> {
     let terminal = ratatui::init();
-    let result = App::new()?.run(terminal);
+    let result = match mouse {
+        true => execute!(stdout(), EnableMouseCapture)
+            .map_err(Into::into)
+            .and_then(|_| app.run(terminal)),
+        false => app.run(terminal),
+    };
+    if mouse {
+        let _ = execute!(stdout(), DisableMouseCapture);
+    }
     ratatui::restore();
     result
 }
+
+/// Play the named puzzle, or let the player pick puzzles from the menu until they quit it.
+fn play_puzzles(config: &Config, db_path: &Path, name: Option<&str>) -> Result<()> {
+    let puzzles = puzzle::load_all(Some(&paths::puzzle_dir()?))?;
+    if let Some(name) = name {
+        let Some(puzzle) = puzzles
+            .into_iter()
+            .find(|p| p.id == name || p.name.eq_ignore_ascii_case(name))
+        else {
+            bail!("There is no puzzle called {}", name);
+        };
+        return play(App::puzzle(config, db_path, puzzle)?, config.layout.mouse);
+    }
+
+    let book = PuzzleBook::new(db_path)?;
+    let mut selected = 0;
+    loop {
+        let menu = PuzzleMenu::new(config, &puzzles, book.all()?, selected)?;
+        let chosen = menu.run(ratatui::init());
+        ratatui::restore();
+        let Some(chosen) = chosen? else {
+            return Ok(());
+        };
+
+        selected = chosen;
+        let puzzle = puzzles[chosen].clone();
+        play(App::puzzle(config, db_path, puzzle)?, config.layout.mouse)?;
+    }
+}
+
+/// Play `games` games with each strategy, all of them if none are named, and print how they did.
+fn run_bench(
+    config: &Config,
+    strategies: &[String],
+    games: usize,
+    first_seed: u64,
+    max_turns: usize,
+    csv: bool,
+) -> Result<()> {
+    let strategies = match strategies.is_empty() {
+        true => Strategy::ALL.to_vec(),
+        false => strategies
+            .iter()
+            .filter_map(|name| Strategy::by_name(name))
+            .collect(),
+    };
+    let summaries = strategies
+        .into_iter()
+        .map(|strategy| {
+            Summary::run(
+                strategy,
+                first_seed,
+                games,
+                config.game.blocks_per_turn,
+                max_turns,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    match csv {
+        true => print!("{}", bench::csv(&summaries)),
+        false => print!("{}", bench::table(&summaries)),
+    }
+    Ok(())
+}
+
+fn print_scores(config: &Config, db_path: &Path) -> Result<()> {
+    let size = config.scoreboard.size;
+    let boards = [
+        ("Personal Best", LocalScoreBoard::new(size, db_path)?),
+        (
+            "Assisted Best",
+            LocalScoreBoard::new_assisted(size, db_path)?,
+        ),
+    ];
+
+    for (title, scoreboard) in boards.iter() {
+        print_high_scores(title, scoreboard.all());
+    }
+
+    // the other modes are only listed once they've been played.
+    for mode in GameMode::ALL.into_iter().skip(1) {
+        for (title, assisted) in [("Best", false), ("Assisted Best", true)] {
+            let scoreboard = LocalScoreBoard::for_mode(size, db_path, mode, assisted)?;
+            if !scoreboard.all().is_empty() {
+                print_high_scores(&format!("{} {}", mode.title(), title), scoreboard.all());
+            }
+        }
+    }
+    for (mode, board) in LocalScoreBoard::other_boards(db_path)? {
+        for (title, assisted) in [("Best", false), ("Assisted Best", true)] {
+            let scoreboard = LocalScoreBoard::for_board(size, db_path, mode, &board, assisted)?;
+            if !scoreboard.all().is_empty() {
+                print_high_scores(
+                    &format!("{} {} {}", mode.title(), board, title),
+                    scoreboard.all(),
+                );
+            }
+        }
+    }
+
+    let daily = DailyChallenge::new(db_path, daily::today())?;
+    print_high_scores(
+        &format!("Daily {}", daily.date()),
+        &daily.leaderboard(size)?,
+    );
+    if let Some(name) = player::resolve_name(config.player.name.as_deref()) {
+        println!("Daily streak for {}: {}", name, daily.streak(&name)?);
+    }
+
+    Ok(())
+}
+
+fn print_high_scores(title: &str, high_scores: &[HighScore]) {
+    println!("{}:", title);
+    if high_scores.is_empty() {
+        println!("  (none)");
+    }
+    for high_score in high_scores {
+        let seed = match high_score.seed {
+            Some(seed) => format!("seed {}", seed),
+            None => String::new(),
+        };
+        let hints = match high_score.hints {
+            0 => String::new(),
+            1 => "  1 hint".to_string(),
+            n => format!("  {} hints", n),
+        };
+        let combo = match &high_score.breakdown {
+            Some(breakdown) if breakdown.best_combo > 1 => {
+                format!("  best combo x{}", breakdown.best_combo)
+            }
+            _ => String::new(),
+        };
+        println!(
+            "  {:>7}  {:<16} {}  {}{}{}",
+            high_score.score,
+            high_score.name,
+            high_score.when.format("%Y-%m-%d %H:%M"),
+            seed,
+            hints,
+            combo
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use serde::Deserialize;
+
+/// The rules for how a game ends. Each mode keeps its own high scores.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum GameMode {
+    /// Play until nothing in the hand fits.
+    #[default]
+    Endless,
+    /// Score as much as possible before the clock runs out.
+    Blitz,
+    /// Score as much as possible with a fixed number of placements.
+    Moves,
+    /// No game over: a hand that doesn't fit anywhere is swapped for one that does.
+    Zen,
+}
+
+impl GameMode {
+    pub const NAMES: [&'static str; 4] = ["endless", "blitz", "moves", "zen"];
+    pub const ALL: [GameMode; 4] = [
+        GameMode::Endless,
+        GameMode::Blitz,
+        GameMode::Moves,
+        GameMode::Zen,
+    ];
+
+    pub fn by_name(name: &str) -> Option<GameMode> {
+        GameMode::ALL.into_iter().find(|m| m.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "endless",
+            GameMode::Blitz => "blitz",
+            GameMode::Moves => "moves",
+            GameMode::Zen => "zen",
+        }
+    }
+
+    /// The name as shown to the player.
+    pub fn title(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::Blitz => "Blitz",
+            GameMode::Moves => "Moves",
+            GameMode::Zen => "Zen",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for (mode, name) in GameMode::ALL.into_iter().zip(GameMode::NAMES) {
+            assert_eq!(mode.name(), name);
+            assert_eq!(GameMode::by_name(name), Some(mode));
+        }
+        assert_eq!(GameMode::by_name("Zen"), None);
+    }
+}
@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, bail};
+
+const APP_NAME: &str = "jcblocktui";
+
+/// Overrides the directory holding the scoreboard, saved games and replays.
+pub const DATA_DIR_ENV: &str = "JCBLOCKTUI_DATA_DIR";
+
+/// Overrides the directory holding the configuration file.
+pub const CONFIG_DIR_ENV: &str = "JCBLOCKTUI_CONFIG_DIR";
+
+const DB_FILE_NAME: &str = "app.db";
+const REPLAY_DIR_NAME: &str = "replays";
+const PUZZLE_DIR_NAME: &str = "puzzles";
+
+/// Where the scoreboard, saved games and replays live.
+///
+/// `JCBLOCKTUI_DATA_DIR` if set, otherwise `$XDG_DATA_HOME/jcblocktui`, falling back to
+/// `~/.local/share/jcblocktui`.
+pub fn data_dir() -> Result<PathBuf> {
+    resolve_dir(
+        env_path(DATA_DIR_ENV),
+        env_path("XDG_DATA_HOME"),
+        env_path("HOME"),
+        ".local/share",
+    )
+}
+
+/// Where the configuration file lives.
+///
+/// `JCBLOCKTUI_CONFIG_DIR` if set, otherwise `$XDG_CONFIG_HOME/jcblocktui`, falling back to
+/// `~/.config/jcblocktui`.
+pub fn config_dir() -> Result<PathBuf> {
+    resolve_dir(
+        env_path(CONFIG_DIR_ENV),
+        env_path("XDG_CONFIG_HOME"),
+        env_path("HOME"),
+        ".config",
+    )
+}
+
+/// The default database, creating the data directory if needed.
+///
+/// Older versions kept the database next to the executable; if one is found there and there is
+/// no database in the data directory yet, it is moved over.
+pub fn default_db_path() -> Result<PathBuf> {
+    let dir = data_dir()?;
+    std::fs::create_dir_all(&dir)?;
+
+    let db_path = dir.join(DB_FILE_NAME);
+    if !db_path.exists()
+        && let Some(legacy) = legacy_db_path()
+        && legacy.is_file()
+    {
+        migrate(&legacy, &db_path)?;
+    }
+
+    Ok(db_path)
+}
+
+/// Where game replays are written, creating the directory if needed.
+pub fn replay_dir() -> Result<PathBuf> {
+    let dir = data_dir()?.join(REPLAY_DIR_NAME);
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Where the player's own puzzles are looked for, next to the configuration file.
+pub fn puzzle_dir() -> Result<PathBuf> {
+    Ok(config_dir()?.join(PUZZLE_DIR_NAME))
+}
+
+fn legacy_db_path() -> Option<PathBuf> {
+    let exe_path = std::env::current_exe().ok()?;
+    Some(exe_path.parent()?.join(DB_FILE_NAME))
+}
+
+fn migrate(from: &Path, to: &Path) -> Result<()> {
+    // a rename fails across file systems, fall back to copying.
+    if std::fs::rename(from, to).is_err() {
+        std::fs::copy(from, to)?;
+
+        // the old location may well be read-only, in which case the copy is left behind.
+        let _ = std::fs::remove_file(from);
+    }
+
+    Ok(())
+}
+
+fn env_path(key: &str) -> Option<PathBuf> {
+    std::env::var_os(key)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+fn resolve_dir(
+    overridden: Option<PathBuf>,
+    xdg: Option<PathBuf>,
+    home: Option<PathBuf>,
+    home_relative: &str,
+) -> Result<PathBuf> {
+    if let Some(dir) = overridden {
+        return Ok(dir);
+    }
+
+    // the XDG spec says relative paths are invalid and should be ignored.
+    if let Some(dir) = xdg.filter(|d| d.is_absolute()) {
+        return Ok(dir.join(APP_NAME));
+    }
+
+    match home {
+        Some(home) => Ok(home.join(home_relative).join(APP_NAME)),
+        None => bail!("Cannot determine a data directory, try setting HOME"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn directory_precedence() {
+        let home = Some(PathBuf::from("/home/allison"));
+        let xdg = Some(PathBuf::from("/xdg/data"));
+        let overridden = Some(PathBuf::from("/somewhere/else"));
+
+        assert_eq!(
+            resolve_dir(overridden, xdg.clone(), home.clone(), ".local/share").unwrap(),
+            PathBuf::from("/somewhere/else")
+        );
+        assert_eq!(
+            resolve_dir(None, xdg, home.clone(), ".local/share").unwrap(),
+            PathBuf::from("/xdg/data/jcblocktui")
+        );
+        assert_eq!(
+            resolve_dir(None, Some(PathBuf::from("relative")), home, ".local/share").unwrap(),
+            PathBuf::from("/home/allison/.local/share/jcblocktui")
+        );
+        assert!(resolve_dir(None, None, None, ".local/share").is_err());
+    }
+}
@@ -0,0 +1,48 @@
+/// Environment variable that names the player, checked before the login name.
+pub const PLAYER_NAME_ENV: &str = "JCBLOCKTUI_NAME";
+
+/// Longest name accepted for the scoreboard.
+pub const MAX_NAME_LENGTH: usize = 16;
+
+/// Work out who is playing without asking them.
+///
+/// An explicit name wins, then `JCBLOCKTUI_NAME`, then the login name from the environment.
+/// Returns `None` when none of those are set, in which case the player is asked for a name once
+/// they earn a high score.
+pub fn resolve_name(explicit: Option<&str>) -> Option<String> {
+    let from_env = |key: &str| std::env::var(key).ok();
+
+    [
+        explicit.map(str::to_owned),
+        from_env(PLAYER_NAME_ENV),
+        from_env("USER"),
+        from_env("USERNAME"),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|name| sanitize(&name))
+    .find(|name| !name.is_empty())
+}
+
+/// Trim whitespace and control characters and cap the length of a name.
+pub fn sanitize(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LENGTH)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names_are_sanitized() {
+        assert_eq!(sanitize("  Allison\t"), "Allison");
+        assert_eq!(sanitize("Bob\u{7}by"), "Bobby");
+        assert_eq!(sanitize(&"x".repeat(40)).len(), MAX_NAME_LENGTH);
+        assert_eq!(resolve_name(Some(" Charlie ")).unwrap(), "Charlie");
+        assert_eq!(resolve_name(Some("   ")), resolve_name(None));
+    }
+}
@@ -0,0 +1,474 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use anyhow::{Context, Result, bail};
+use chrono::Utc;
+use jcblocks::{block::Block, block::Point, game::Game};
+use rusqlite::{Connection, params};
+use serde::Deserialize;
+
+use crate::board::{MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+use crate::config::{MAX_BLOCKS_PER_TURN, NUM_BLOCKS_PER_TURN};
+use crate::generator::BlockGenerator;
+use crate::save::build_canvas;
+use crate::scoring::Scorer;
+use crate::session::{POINTS_PER_LINE_CLEAR, Session, extent};
+use crate::shapes;
+
+/// The puzzles that come with the game, as `(id, contents)`.
+const BUILT_IN: [(&str, &str); 4] = [
+    (
+        "01-first-steps",
+        include_str!("../puzzles/01-first-steps.toml"),
+    ),
+    (
+        "02-crossroads",
+        include_str!("../puzzles/02-crossroads.toml"),
+    ),
+    (
+        "03-tight-squeeze",
+        include_str!("../puzzles/03-tight-squeeze.toml"),
+    ),
+    (
+        "04-combo-breaker",
+        include_str!("../puzzles/04-combo-breaker.toml"),
+    ),
+];
+
+const EXTENSION: &str = "toml";
+
+/// What has to be done to solve a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Goal {
+    /// Leave nothing on the board.
+    ClearBoard,
+    /// Clear at least this many rows and columns.
+    Lines(usize),
+    /// Score at least this many points, combo bonuses included.
+    Score(usize),
+}
+
+/// How an attempt at a puzzle is going.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Solved,
+    Failed,
+}
+
+/// A puzzle as written in its file.
+///
+/// ```toml
+/// name = "Crossroads"
+/// description = "A row and a column can be cleared at the same time."
+/// goal = "clear-board"    # or { lines = 4 } or { score = 600 }
+/// moves = 1               # optional limit on placements
+/// hand = 3                # blocks shown at a time
+///
+/// # one line per row, top to bottom: ● or # for a filled square, ◌ or . for an empty one.
+/// # from 5 to 20 rows and columns.
+/// board = """
+/// ◌◌◌◌◌◌◌◌
+/// ◌◌◌●◌◌◌◌
+/// ●●●◌●●●●
+/// ◌◌◌●◌◌◌◌
+/// ◌◌◌◌◌◌◌◌
+/// """
+///
+/// # dealt in this order, drawn the same way as the board.
+/// blocks = ["●", "●●\n●●"]
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PuzzleFile {
+    name: String,
+    #[serde(default)]
+    description: String,
+    goal: Goal,
+    moves: Option<usize>,
+    #[serde(default = "default_hand")]
+    hand: usize,
+    board: String,
+    blocks: Vec<String>,
+}
+
+fn default_hand() -> usize {
+    NUM_BLOCKS_PER_TURN
+}
+
+/// A hand-made starting board and the blocks to clear it with.
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    /// The file name without its extension, which progress is recorded under.
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub goal: Goal,
+    /// Placements allowed, if limited.
+    pub moves: Option<usize>,
+    /// Number of blocks dealt at a time.
+    pub hand: usize,
+    pub rows: usize,
+    pub columns: usize,
+    /// Which squares start occupied, in the same order as the canvas.
+    pub occupied: Vec<bool>,
+    pub blocks: Vec<Block>,
+}
+
+impl Puzzle {
+    pub fn parse(id: &str, contents: &str) -> Result<Puzzle> {
+        let file: PuzzleFile = toml::from_str(contents)?;
+        if !(1..=MAX_BLOCKS_PER_TURN).contains(&file.hand) {
+            bail!(
+                "hand must be from 1 to {}, got {}",
+                MAX_BLOCKS_PER_TURN,
+                file.hand
+            );
+        }
+        if file.moves == Some(0) {
+            bail!("moves must be at least 1");
+        }
+        if file.blocks.is_empty() {
+            bail!("blocks must have at least one block");
+        }
+
+        let (rows, columns, squares) = parse_grid(&file.board).context("Invalid board")?;
+        check_size(rows, columns).context("Invalid board")?;
+        let mut occupied = vec![false; rows * columns];
+        for p in squares {
+            occupied[p.y as usize * columns + p.x as usize] = true;
+        }
+        // checked now, so a bad board is reported when the puzzle is loaded rather than played.
+        build_canvas(rows, columns, &occupied)?;
+
+        let mut blocks = Vec::new();
+        for (i, grid) in file.blocks.iter().enumerate() {
+            let (_, _, squares) =
+                parse_grid(grid).with_context(|| format!("Invalid block {}", i + 1))?;
+            let Some(block) = shapes::from_shape(&squares) else {
+                bail!("Block {} is not a shape the game can deal", i + 1);
+            };
+            let (min, max) = extent(&block);
+            if (max.x - min.x) as usize >= columns || (max.y - min.y) as usize >= rows {
+                bail!("Block {} is bigger than the board", i + 1);
+            }
+            blocks.push(block);
+        }
+
+        Ok(Puzzle {
+            id: id.to_string(),
+            name: file.name,
+            description: file.description,
+            goal: file.goal,
+            moves: file.moves,
+            hand: file.hand,
+            rows,
+            columns,
+            occupied,
+            blocks,
+        })
+    }
+
+    /// Read a puzzle from a file, named after the file.
+    pub fn load(path: &Path) -> Result<Puzzle> {
+        let id = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read puzzle {}", path.display()))?;
+
+        Puzzle::parse(&id, &contents).with_context(|| format!("Invalid puzzle {}", path.display()))
+    }
+
+    /// A fresh attempt at the puzzle.
+    pub fn session(&self) -> Session {
+        let game = Game {
+            canvas: build_canvas(self.rows, self.columns, &self.occupied)
+                .expect("The board is checked when the puzzle is parsed."),
+            score: 0,
+        };
+
+        Session::start(
+            game,
+            BlockGenerator::sequence(self.blocks.clone()),
+            self.hand,
+        )
+    }
+
+    /// Whether the goal has been reached after `moves` placements, or can't be any more.
+    pub fn status(&self, session: &Session, scorer: &Scorer, moves: usize) -> Status {
+        let reached = match self.goal {
+            // an untouched board doesn't count, even if it starts out empty.
+            Goal::ClearBoard => moves > 0 && session.is_clear(),
+            Goal::Lines(lines) => scorer.breakdown().lines / POINTS_PER_LINE_CLEAR >= lines,
+            Goal::Score(points) => scorer.total() >= points,
+        };
+
+        if reached {
+            Status::Solved
+        } else if session.is_game_over() || self.moves.is_some_and(|limit| moves >= limit) {
+            Status::Failed
+        } else {
+            Status::Playing
+        }
+    }
+}
+
+/// The goal and move limit in words, e.g. "Clear the board in 3 moves".
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.goal {
+            Goal::ClearBoard => write!(f, "Clear the board")?,
+            Goal::Lines(1) => write!(f, "Clear a line")?,
+            Goal::Lines(lines) => write!(f, "Clear {} lines", lines)?,
+            Goal::Score(points) => write!(f, "Score {} points", points)?,
+        }
+        match self.moves {
+            Some(1) => write!(f, " in 1 move"),
+            Some(moves) => write!(f, " in {} moves", moves),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Puzzle boards have the same bounds as any other board, which the screen is laid out for.
+fn check_size(rows: usize, columns: usize) -> Result<()> {
+    for (what, size) in [("rows", rows), ("columns", columns)] {
+        if !(MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size) {
+            bail!(
+                "Must have from {} to {} {}, got {}",
+                MIN_BOARD_SIZE,
+                MAX_BOARD_SIZE,
+                what,
+                size
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a board or block drawn as text, one line per row from the top. Returns the number of
+/// rows and columns, and the occupied squares with row 0 at the bottom.
+fn parse_grid(grid: &str) -> Result<(usize, usize, Vec<Point>)> {
+    let lines: Vec<&str> = grid
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let columns = lines.first().map_or(0, |line| line.chars().count());
+
+    let mut squares = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if line.chars().count() != columns {
+            bail!("Every row must be {} squares wide", columns);
+        }
+
+        let y = (lines.len() - 1 - i) as i32;
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '●' | '#' => squares.push(Point { x: x as i32, y }),
+                '◌' | '.' => {}
+                _ => bail!("Unexpected character '{}', use ● or # and ◌ or .", c),
+            }
+        }
+    }
+
+    Ok((lines.len(), columns, squares))
+}
+
+/// The built-in puzzles followed by any in `dir`, in order of their ids. A file with the same
+/// name as a built-in puzzle replaces it.
+pub fn load_all(dir: Option<&Path>) -> Result<Vec<Puzzle>> {
+    let mut puzzles = BUILT_IN
+        .iter()
+        .map(|(id, contents)| Puzzle::parse(id, contents))
+        .collect::<Result<Vec<_>>>()?;
+
+    if let Some(dir) = dir.filter(|dir| dir.is_dir()) {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == EXTENSION) {
+                let puzzle = Puzzle::load(&path)?;
+                puzzles.retain(|p| p.id != puzzle.id);
+                puzzles.push(puzzle);
+            }
+        }
+    }
+
+    puzzles.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(puzzles)
+}
+
+/// How a player has done at one puzzle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Attempts played to the end, solved or not.
+    pub attempts: u32,
+    /// The fewest placements it has been solved in.
+    pub best_moves: Option<usize>,
+}
+
+impl Progress {
+    pub fn is_solved(&self) -> bool {
+        self.best_moves.is_some()
+    }
+}
+
+/// Keeps track of which puzzles have been solved, and in how few moves.
+#[derive(Debug)]
+pub struct PuzzleBook {
+    db_conn: Connection,
+}
+
+impl PuzzleBook {
+    pub fn new<P>(connection_string: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let db_conn = Connection::open(connection_string)?;
+        db_conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS puzzle_progress (
+                puzzle TEXT PRIMARY KEY,
+                attempts INTEGER NOT NULL,
+                best_moves INTEGER,
+                "when" TEXT NOT NULL
+            )"#,
+            (),
+        )?;
+
+        Ok(Self { db_conn })
+    }
+
+    /// Record a finished attempt at the puzzle with `id`, and the number of moves it took if it
+    /// was solved.
+    pub fn record(&self, id: &str, solved_in: Option<usize>) -> Result<()> {
+        self.db_conn.execute(
+            r#"
+            INSERT INTO puzzle_progress (puzzle, attempts, best_moves, "when")
+            VALUES ((?1), 1, (?2), (?3))
+            ON CONFLICT (puzzle) DO UPDATE SET
+                attempts = attempts + 1,
+                best_moves = CASE
+                    WHEN best_moves IS NULL THEN excluded.best_moves
+                    WHEN excluded.best_moves IS NULL THEN best_moves
+                    ELSE MIN(best_moves, excluded.best_moves)
+                END,
+                "when" = excluded."when"
+        "#,
+            params![id, solved_in, Utc::now().to_rfc3339()],
+        )?;
+
+        Ok(())
+    }
+
+    /// Progress on every puzzle that has been attempted, by id.
+    pub fn all(&self) -> Result<HashMap<String, Progress>> {
+        let mut statement = self
+            .db_conn
+            .prepare("SELECT puzzle, attempts, best_moves FROM puzzle_progress")?;
+
+        let found = statement
+            .query_map((), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    Progress {
+                        attempts: row.get(1)?,
+                        best_moves: row.get(2)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+        Ok(found)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::Move;
+
+    #[test]
+    fn built_in_puzzles_can_be_solved() {
+        let puzzles = load_all(None).unwrap();
+        assert_eq!(puzzles.len(), BUILT_IN.len());
+
+        // the 2x2 square is the second block, with the line of three as a decoy.
+        let tight_squeeze = puzzles.iter().find(|p| p.id == "03-tight-squeeze").unwrap();
+        assert_eq!(tight_squeeze.to_string(), "Clear 4 lines in 1 move");
+        let mut session = tight_squeeze.session();
+        let mut scorer = Scorer::default();
+        let placed = session
+            .apply(&Move {
+                selected: 1,
+                row: 0,
+                column: 0,
+            })
+            .unwrap();
+        scorer.score(placed.lines_cleared, session.is_clear());
+        assert_eq!(tight_squeeze.status(&session, &scorer, 1), Status::Solved);
+
+        let mut session = tight_squeeze.session();
+        session
+            .apply(&Move {
+                selected: 0,
+                row: 5,
+                column: 4,
+            })
+            .unwrap();
+        assert_eq!(
+            tight_squeeze.status(&session, &Scorer::default(), 1),
+            Status::Failed
+        );
+    }
+
+    #[test]
+    fn progress_keeps_the_best_solve() {
+        let book = PuzzleBook::new(":memory:").unwrap();
+        book.record("crossroads", None).unwrap();
+        book.record("crossroads", Some(3)).unwrap();
+        book.record("crossroads", Some(5)).unwrap();
+        book.record("crossroads", None).unwrap();
+
+        let progress = book.all().unwrap().remove("crossroads").unwrap();
+        assert_eq!(progress.attempts, 4);
+        assert_eq!(progress.best_moves, Some(3));
+        assert!(
+            Puzzle::parse(
+                "bad",
+                "name = \"x\"\ngoal = \"clear-board\"\nboard = \"●◌\\n●\"\nblocks = [\"●\"]"
+            )
+            .is_err()
+        );
+        assert!(
+            Puzzle::parse(
+                "bad",
+                "name = \"x\"\ngoal = \"clear-board\"\nboard = \"\"\nblocks = [\"●\"]"
+            )
+            .is_err()
+        );
+
+        // starting from an empty board is fine, as long as it's as big as any other board.
+        let empty = "◌◌◌◌◌\\n".repeat(5);
+        let empty = Puzzle::parse(
+            "empty",
+            &format!(
+                "name = \"x\"\ngoal = {{ lines = 1 }}\nboard = \"{empty}\"\nblocks = [\"●●●●●\"]"
+            ),
+        )
+        .unwrap();
+        assert_eq!((empty.rows, empty.columns), (5, 5));
+
+        let narrow = "◌◌◌◌\\n".repeat(5);
+        let err = Puzzle::parse(
+            "narrow",
+            &format!(
+                "name = \"x\"\ngoal = {{ lines = 1 }}\nboard = \"{narrow}\"\nblocks = [\"●\"]"
+            ),
+        )
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("Invalid board"));
+    }
+}
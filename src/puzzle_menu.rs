@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::{
+    DefaultTerminal, Frame,
+    buffer::Buffer,
+    layout::{Constraint, Flex, Layout, Rect},
+    style::Stylize,
+    symbols::border,
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Paragraph, Widget},
+};
+
+use crate::config::Config;
+use crate::keymap::{Action, Keymap};
+use crate::puzzle::{Progress, Puzzle};
+use crate::theme::Theme;
+
+/// Lists the puzzles and how the player has done at each, until one is picked.
+#[derive(Debug)]
+pub struct PuzzleMenu<'a> {
+    puzzles: &'a [Puzzle],
+    progress: HashMap<String, Progress>,
+    selected: usize,
+    keymap: Keymap,
+    theme: Theme,
+    chosen: Option<usize>,
+    exit: bool,
+}
+
+impl<'a> PuzzleMenu<'a> {
+    /// A menu of `puzzles` with the one at `selected` highlighted.
+    pub fn new(
+        config: &Config,
+        puzzles: &'a [Puzzle],
+        progress: HashMap<String, Progress>,
+        selected: usize,
+    ) -> Result<Self> {
+        Ok(PuzzleMenu {
+            puzzles,
+            progress,
+            selected: selected.min(puzzles.len().saturating_sub(1)),
+            keymap: config.keymap()?,
+            theme: config.theme()?,
+            chosen: None,
+            exit: false,
+        })
+    }
+
+    /// Show the menu, returning the index of the puzzle picked or `None` if the player quit.
+    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<Option<usize>> {
+        while !self.exit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key_event) = event::read()?
+                && key_event.kind == KeyEventKind::Press
+            {
+                self.handle_key_event(key_event);
+            }
+        }
+
+        Ok(self.chosen)
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        match (key_event.code, self.keymap.action(&key_event)) {
+            (KeyCode::Enter, _) | (_, Some(Action::Place)) => {
+                self.chosen = (!self.puzzles.is_empty()).then_some(self.selected);
+                self.exit = true;
+            }
+            (KeyCode::Esc, _) | (_, Some(Action::Quit)) => self.exit = true,
+            (_, Some(Action::MoveUp | Action::CyclePrevious)) => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            (_, Some(Action::MoveDown | Action::CycleNext)) => {
+                self.selected = (self.selected + 1).min(self.puzzles.len().saturating_sub(1));
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Widget for &PuzzleMenu<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let solved = self
+            .puzzles
+            .iter()
+            .filter(|p| self.progress.get(&p.id).is_some_and(Progress::is_solved))
+            .count();
+        let instructions = Line::from(vec![
+            Span::from(" Quit "),
+            Span::styled(
+                format!("<{}> ", self.keymap.describe(&[Action::Quit])),
+                self.theme.key_hint,
+            ),
+            Span::from(" Choose "),
+            Span::styled(
+                format!(
+                    "<{}> ",
+                    self.keymap.describe(&[Action::MoveUp, Action::MoveDown])
+                ),
+                self.theme.key_hint,
+            ),
+            Span::from(" Play "),
+            Span::styled(
+                format!("<{}> ", self.keymap.describe(&[Action::Place])),
+                self.theme.key_hint,
+            ),
+        ]);
+        let block = Block::bordered()
+            .title(Line::from(" Block TUI - Puzzles ".bold()).left_aligned())
+            .title(Line::from(format!(" Solved: {} of {} ", solved, self.puzzles.len())).centered())
+            .title_bottom(instructions.centered())
+            .border_set(border::THICK)
+            .border_type(BorderType::Rounded);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let lines: Vec<Line> = self
+            .puzzles
+            .iter()
+            .enumerate()
+            .map(|(i, puzzle)| {
+                let progress = self.progress.get(&puzzle.id).cloned().unwrap_or_default();
+                let result = match progress.best_moves {
+                    Some(1) => "best 1 move".to_string(),
+                    Some(moves) => format!("best {} moves", moves),
+                    None if progress.attempts > 0 => format!("{} tries", progress.attempts),
+                    None => String::new(),
+                };
+                let line = Line::from(format!(
+                    "{} {:<20} {:<32} {:>13}",
+                    if progress.is_solved() { "✓" } else { " " },
+                    puzzle.name,
+                    puzzle.to_string(),
+                    result
+                ));
+                match i == self.selected {
+                    true => line.patch_style(self.theme.selected_block),
+                    false => line.patch_style(self.theme.scoreboard),
+                }
+            })
+            .collect();
+
+        let [list_area, description_area] = Layout::vertical([
+            Constraint::Length(lines.len() as u16),
+            Constraint::Length(2),
+        ])
+        .spacing(1)
+        .flex(Flex::Center)
+        .areas(inner);
+
+        Paragraph::new(Text::from(lines))
+            .centered()
+            .render(list_area, buf);
+        if let Some(puzzle) = self.puzzles.get(self.selected) {
+            Paragraph::new(puzzle.description.as_str())
+                .style(self.theme.key_hint)
+                .centered()
+                .render(description_area, buf);
+        }
+    }
+}
@@ -0,0 +1,616 @@
+use std::{
+    fmt::Display,
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use jcblocks::{
+    block::{Block, Point},
+    canvas::{Canvas, PointStatus},
+    game::Game,
+};
+
+use crate::board::restore_obstacles;
+use crate::save::{
+    build_canvas, decode_blocks, decode_canvas, decode_obstacles, encode_blocks, encode_canvas,
+    encode_obstacles,
+};
+use crate::scoring::{self, Breakdown, Scorer};
+use crate::session::POINTS_PER_LINE_CLEAR;
+
+const HEADER: &str = "jcblocktui-replay 1";
+const EXTENSION: &str = "replay";
+
+/// Playback speeds, as multiples of real time.
+const SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const NORMAL_SPEED: usize = 2;
+
+/// However long the player thought about a move, playback never waits longer than this.
+const MAX_STEP: Duration = Duration::from_secs(2);
+const MIN_STEP: Duration = Duration::from_millis(100);
+
+/// Everything that happened in one game, enough to watch it again.
+///
+/// Replays are plain text, one line per event:
+///
+/// ```text
+/// jcblocktui-replay 1
+/// seed 1234
+/// started 2025-03-01T09:30:00+00:00
+/// board 8 8 0000000000...
+/// score 0
+/// obstacles 3 17 40
+/// hand 0,0 1,0;0,0 0,1 1,1
+/// place 1 3 4 2150
+/// undo 3020
+/// ```
+///
+/// A placement is the index of the block in the hand, the row and column it went to, and the
+/// milliseconds since the game started. The obstacles line is left out when there are none.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    pub rows: usize,
+    pub columns: usize,
+    /// The board the game started from, which is only ever not empty for resumed games.
+    pub occupied: Vec<bool>,
+    pub score: usize,
+    /// Squares that can't be cleared, as indexes into the board.
+    pub obstacles: Vec<usize>,
+    pub started: DateTime<Utc>,
+    pub events: Vec<ReplayEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ReplayEvent {
+    /// A new hand was dealt.
+    Hand(Vec<Block>),
+    /// The block at `selected` in the hand was placed, `at` milliseconds into the game.
+    Place {
+        selected: usize,
+        row: i32,
+        column: i32,
+        at: u64,
+    },
+    Undo {
+        at: u64,
+    },
+    Redo {
+        at: u64,
+    },
+}
+
+impl Replay {
+    /// Start recording a game from its current board and hand.
+    pub fn new(game: &Game, blocks: &[Block], seed: u64) -> Self {
+        Replay {
+            seed,
+            rows: game.canvas.rows,
+            columns: game.canvas.columns,
+            occupied: game
+                .canvas
+                .contents()
+                .iter()
+                .map(|p| matches!(p, PointStatus::Occupied))
+                .collect(),
+            score: game.score,
+            obstacles: Vec::new(),
+            started: Utc::now(),
+            events: vec![ReplayEvent::Hand(blocks.to_vec())],
+        }
+    }
+
+    /// Note the squares that can't be cleared.
+    pub fn with_obstacles(mut self, obstacles: &[usize]) -> Self {
+        self.obstacles = obstacles.to_vec();
+        self
+    }
+
+    pub fn hand(&mut self, blocks: &[Block]) {
+        self.events.push(ReplayEvent::Hand(blocks.to_vec()));
+    }
+
+    pub fn place(&mut self, selected: usize, row: i32, column: i32) {
+        let at = self.elapsed();
+        self.events.push(ReplayEvent::Place {
+            selected,
+            row,
+            column,
+            at,
+        });
+    }
+
+    pub fn undo(&mut self) {
+        let at = self.elapsed();
+        self.events.push(ReplayEvent::Undo { at });
+    }
+
+    pub fn redo(&mut self) {
+        let at = self.elapsed();
+        self.events.push(ReplayEvent::Redo { at });
+    }
+
+    /// Was anything actually played?
+    pub fn is_empty(&self) -> bool {
+        !self
+            .events
+            .iter()
+            .any(|e| matches!(e, ReplayEvent::Place { .. }))
+    }
+
+    fn elapsed(&self) -> u64 {
+        Utc::now()
+            .signed_duration_since(self.started)
+            .num_milliseconds()
+            .max(0) as u64
+    }
+
+    /// Write the replay to `dir`, named after when the game started.
+    ///
+    /// Games dealt from the same seed can start in the same second, like a daily attempt and the
+    /// practice game after it, so a name that is taken gets a number on the end rather than being
+    /// overwritten. `_` sorts after `.`, so the later game is still the latest.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf> {
+        let stem = format!("{}-{}", self.started.format("%Y%m%d-%H%M%S"), self.seed);
+        let mut taken = 0;
+        loop {
+            let path = match taken {
+                0 => dir.join(format!("{}.{}", stem, EXTENSION)),
+                n => dir.join(format!("{}_{}.{}", stem, n + 1, EXTENSION)),
+            };
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(self.to_string().as_bytes())
+                        .with_context(|| format!("Cannot write replay {}", path.display()))?;
+                    return Ok(path);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => taken += 1,
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Cannot write replay {}", path.display()));
+                }
+            }
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Replay> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read replay {}", path.display()))?;
+
+        contents
+            .parse()
+            .with_context(|| format!("Invalid replay {}", path.display()))
+    }
+
+    /// The most recent replay in `dir`, if there are any.
+    pub fn latest(dir: &Path) -> Result<Option<PathBuf>> {
+        // names start with the date, so the last one alphabetically is the newest.
+        let mut latest = None;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == EXTENSION)
+                && latest.as_ref().is_none_or(|l| path > *l)
+            {
+                latest = Some(path);
+            }
+        }
+
+        Ok(latest)
+    }
+
+    /// Play the game through, giving the state before each move and the state it ended in.
+    pub fn frames(&self) -> Result<Vec<Frame>> {
+        let mut game = Game {
+            canvas: build_canvas(self.rows, self.columns, &self.occupied)?,
+            score: self.score,
+        };
+        let mut blocks = Vec::new();
+        // combos weren't recorded for whatever happened before the replay starts.
+        let mut scorer = Scorer::resume(0, Breakdown::lines_only(self.score));
+        let mut undo: Vec<(Canvas, usize, Vec<Block>, Scorer)> = Vec::new();
+        let mut redo: Vec<(Canvas, usize, Vec<Block>, Scorer)> = Vec::new();
+
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut at = 0;
+        for event in self.events.iter() {
+            if let ReplayEvent::Hand(hand) = event {
+                blocks = hand.clone();
+                continue;
+            }
+
+            let mut frame = Frame {
+                canvas: game.canvas.clone(),
+                score: game.score,
+                blocks: blocks.clone(),
+                scorer: scorer.clone(),
+                selected: 0,
+                cursor: None,
+                at,
+            };
+            let current = (
+                game.canvas.clone(),
+                game.score,
+                blocks.clone(),
+                scorer.clone(),
+            );
+            match event {
+                ReplayEvent::Place {
+                    selected,
+                    row,
+                    column,
+                    at: placed_at,
+                } => {
+                    let Some(block) = blocks.get(*selected) else {
+                        bail!(
+                            "Move at {}ms places a block that isn't in the hand",
+                            placed_at
+                        );
+                    };
+                    let before = game.score;
+                    game.maybe_place_block(block, *row, *column)
+                        .map_err(|_| anyhow!("Move at {}ms doesn't fit", placed_at))?;
+                    restore_obstacles(&mut game.canvas, &self.obstacles);
+                    scorer.score(
+                        (game.score - before) / POINTS_PER_LINE_CLEAR,
+                        scoring::is_empty(&game.canvas, &self.obstacles),
+                    );
+                    blocks.remove(*selected);
+
+                    frame.selected = *selected;
+                    frame.cursor = Some(Point {
+                        x: *column,
+                        y: *row,
+                    });
+                    undo.push(current);
+                    redo.clear();
+                    at = *placed_at;
+                }
+                ReplayEvent::Undo { at: undone_at } => {
+                    let Some(previous) = undo.pop() else {
+                        bail!("Undo at {}ms has nothing to undo", undone_at);
+                    };
+                    (game.canvas, game.score, blocks, scorer) = previous;
+                    redo.push(current);
+                    at = *undone_at;
+                }
+                ReplayEvent::Redo { at: redone_at } => {
+                    let Some(next) = redo.pop() else {
+                        bail!("Redo at {}ms has nothing to redo", redone_at);
+                    };
+                    (game.canvas, game.score, blocks, scorer) = next;
+                    undo.push(current);
+                    at = *redone_at;
+                }
+                ReplayEvent::Hand(_) => unreachable!("Hands are handled above."),
+            }
+            frames.push(frame);
+        }
+
+        frames.push(Frame {
+            canvas: game.canvas,
+            score: game.score,
+            blocks,
+            scorer,
+            selected: 0,
+            cursor: None,
+            at,
+        });
+
+        if frames.iter().any(|f| f.blocks.is_empty()) {
+            bail!("Replay has a turn without any blocks");
+        }
+
+        Ok(frames)
+    }
+}
+
+impl Display for Replay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "seed {}", self.seed)?;
+        writeln!(f, "started {}", self.started.to_rfc3339())?;
+        writeln!(
+            f,
+            "board {} {} {}",
+            self.rows,
+            self.columns,
+            encode_canvas(&self.occupied)
+        )?;
+        writeln!(f, "score {}", self.score)?;
+        if !self.obstacles.is_empty() {
+            writeln!(f, "obstacles {}", encode_obstacles(&self.obstacles))?;
+        }
+
+        for event in self.events.iter() {
+            match event {
+                ReplayEvent::Hand(blocks) => writeln!(f, "hand {}", encode_blocks(blocks))?,
+                ReplayEvent::Place {
+                    selected,
+                    row,
+                    column,
+                    at,
+                } => writeln!(f, "place {} {} {} {}", selected, row, column, at)?,
+                ReplayEvent::Undo { at } => writeln!(f, "undo {}", at)?,
+                ReplayEvent::Redo { at } => writeln!(f, "redo {}", at)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Replay {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut lines = s.lines();
+        if lines.next() != Some(HEADER) {
+            bail!("Not a replay, or from a newer version");
+        }
+
+        let mut field = |name: &str| -> Result<String> {
+            match lines.next().and_then(|l| l.strip_prefix(name)) {
+                Some(value) => Ok(value.trim().to_string()),
+                None => bail!("Expected {}", name.trim()),
+            }
+        };
+        let seed = field("seed ")?.parse()?;
+        let started = DateTime::parse_from_rfc3339(&field("started ")?)?.with_timezone(&Utc);
+        let board = field("board ")?;
+        let score = field("score ")?.parse()?;
+
+        let (rows, columns, occupied) = match board.split_whitespace().collect::<Vec<_>>()[..] {
+            [rows, columns, occupied] => {
+                (rows.parse()?, columns.parse()?, decode_canvas(occupied)?)
+            }
+            _ => bail!("Unexpected board '{}'", board),
+        };
+
+        let mut obstacles = Vec::new();
+        let mut events = Vec::new();
+        let mut last_at = 0;
+        for line in lines.filter(|l| !l.trim().is_empty()) {
+            let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let numbers = || -> Result<Vec<i64>> {
+                rest.split_whitespace()
+                    .map(|n| Ok(n.parse()?))
+                    .collect::<Result<Vec<i64>>>()
+            };
+            // only the obstacles can come before the first hand.
+            if kind == "obstacles" && events.is_empty() {
+                obstacles = decode_obstacles(rest)?;
+                continue;
+            }
+            let event = match (kind, numbers().as_deref()) {
+                ("hand", _) => ReplayEvent::Hand(decode_blocks(rest)?),
+                ("place", Ok([selected, row, column, at])) => ReplayEvent::Place {
+                    selected: *selected as usize,
+                    row: *row as i32,
+                    column: *column as i32,
+                    at: *at as u64,
+                },
+                ("undo", Ok([at])) => ReplayEvent::Undo { at: *at as u64 },
+                ("redo", Ok([at])) => ReplayEvent::Redo { at: *at as u64 },
+                _ => bail!("Unexpected line '{}'", line),
+            };
+            // the clock only runs forwards, playback can't go back in time.
+            if let ReplayEvent::Place { at, .. }
+            | ReplayEvent::Undo { at }
+            | ReplayEvent::Redo { at } = event
+            {
+                if at < last_at {
+                    bail!("Event at {}ms comes after one at {}ms", at, last_at);
+                }
+                last_at = at;
+            }
+            events.push(event);
+        }
+
+        Ok(Replay {
+            seed,
+            rows,
+            columns,
+            occupied,
+            score,
+            obstacles,
+            started,
+            events,
+        })
+    }
+}
+
+/// The board and hand at one point in a replay.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub canvas: Canvas,
+    /// The points `jcblocks` gave, before any combo bonuses.
+    pub score: usize,
+    pub blocks: Vec<Block>,
+    pub scorer: Scorer,
+    /// The block about to be placed.
+    pub selected: usize,
+    /// Where it is about to be placed, if the next thing that happens is a placement.
+    pub cursor: Option<Point>,
+    /// Milliseconds since the game started.
+    pub at: u64,
+}
+
+/// Steps through a replay's frames in real time, or faster or slower.
+#[derive(Debug)]
+pub struct Playback {
+    frames: Vec<Frame>,
+    position: usize,
+    playing: bool,
+    speed: usize,
+}
+
+impl Playback {
+    pub fn new(frames: Vec<Frame>) -> Self {
+        Playback {
+            frames,
+            position: 0,
+            playing: true,
+            speed: NORMAL_SPEED,
+        }
+    }
+
+    pub fn frame(&self) -> &Frame {
+        &self.frames[self.position]
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position + 1 >= self.frames.len()
+    }
+
+    /// Playback speed as a multiple of real time.
+    pub fn speed(&self) -> f64 {
+        SPEEDS[self.speed]
+    }
+
+    pub fn toggle_pause(&mut self) {
+        // playing from the end starts over.
+        if !self.playing && self.is_finished() {
+            self.position = 0;
+        }
+        self.playing = !self.playing;
+    }
+
+    pub fn seek(&mut self, position: usize) {
+        self.position = position.min(self.frames.len() - 1);
+    }
+
+    pub fn step_forward(&mut self) {
+        self.seek(self.position + 1);
+    }
+
+    pub fn step_back(&mut self) {
+        self.seek(self.position.saturating_sub(1));
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// How long to show the current frame before moving on, or `None` if playback is paused.
+    pub fn next_delay(&self) -> Option<Duration> {
+        if !self.playing || self.is_finished() {
+            return None;
+        }
+
+        let gap = self.frames[self.position + 1]
+            .at
+            .saturating_sub(self.frame().at);
+        let delay = Duration::from_millis(gap).div_f64(self.speed());
+        Some(delay.clamp(MIN_STEP, MAX_STEP))
+    }
+
+    /// Move on to the next frame, pausing at the end.
+    pub fn advance(&mut self) {
+        self.step_forward();
+        if self.is_finished() {
+            self.playing = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn recorded() -> Replay {
+        let game = Game::default();
+        let mut replay = Replay::new(&game, &[Block::line(3), Block::rectangle(2, 2)], 42);
+        replay.place(1, 0, 0);
+        replay.place(0, 5, 5);
+        replay.hand(&[Block::tee(), Block::rectangle(1, 1)]);
+        replay.undo();
+        replay.redo();
+        replay
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        let replay = recorded();
+        let decoded: Replay = replay.to_string().parse().unwrap();
+        assert_eq!(decoded.to_string(), replay.to_string());
+        assert_eq!(decoded.seed, 42);
+        assert_eq!(decoded.events.len(), 6);
+
+        assert!("jcblocktui-replay 2\n".parse::<Replay>().is_err());
+        let truncated = replay.to_string().replace("place 1 0 0", "place 1 0");
+        assert!(truncated.parse::<Replay>().is_err());
+
+        let mut skewed = recorded();
+        if let ReplayEvent::Place { at, .. } = &mut skewed.events[1] {
+            *at = 60_000;
+        }
+        assert!(skewed.to_string().parse::<Replay>().is_err());
+    }
+
+    #[test]
+    fn replays_started_together_are_all_kept() {
+        let dir =
+            std::env::temp_dir().join(format!("jcblocktui-replay-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let replay = recorded();
+        let first = replay.save(&dir).unwrap();
+        let second = replay.save(&dir).unwrap();
+        assert_ne!(first, second);
+        assert!(first.is_file());
+        assert_eq!(Replay::latest(&dir).unwrap(), Some(second));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn frames_follow_undo_and_redo() {
+        let frames = recorded().frames().unwrap();
+
+        // before each of the two placements, the undo and the redo, and the end
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].cursor, Some(Point { x: 0, y: 0 }));
+        assert_eq!(frames[0].selected, 1);
+        assert_eq!(frames[2].blocks.len(), 2);
+        assert_eq!(frames[3].blocks.len(), 1);
+        assert_eq!(
+            format!("{:?}", frames[4].canvas),
+            format!("{:?}", frames[2].canvas)
+        );
+
+        let mut playback = Playback::new(frames);
+        playback.faster();
+        assert_eq!(playback.speed(), 2.0);
+        while playback.is_playing() {
+            assert!(playback.next_delay().unwrap() >= MIN_STEP);
+            playback.advance();
+        }
+        assert_eq!(playback.position(), 4);
+        assert!(playback.next_delay().is_none());
+    }
+}
@@ -0,0 +1,505 @@
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, Utc};
+use jcblocks::{
+    block::{Block, Point},
+    canvas::{Canvas, PointStatus},
+    game::Game,
+};
+use rusqlite::{Connection, OptionalExtension, params};
+
+use crate::block_index::BlockIndex;
+use crate::db::add_column_if_missing;
+use crate::generator::BlockGenerator;
+use crate::mode::GameMode;
+use crate::scoring::{Breakdown, Scorer};
+use crate::session::Session;
+use crate::shapes;
+
+/// A snapshot of an in-progress game.
+#[derive(Debug, Clone)]
+pub struct SavedGame {
+    pub rows: usize,
+    pub columns: usize,
+    pub occupied: Vec<bool>,
+    /// Squares that can't be cleared, as indexes into the board.
+    pub obstacles: Vec<usize>,
+    pub score: usize,
+    pub blocks: Vec<Block>,
+    pub selected: usize,
+    pub assisted: bool,
+    /// Whether undo could be used, which a resumed game keeps to.
+    pub allow_undo: bool,
+    pub hints: u32,
+    pub seed: Option<u64>,
+    pub rng_position: Option<u128>,
+    /// Placements in a row that have cleared lines.
+    pub streak: usize,
+    /// Where the points came from, unknown for games saved before combos were scored.
+    pub breakdown: Option<Breakdown>,
+    pub mode: GameMode,
+    /// Placements made so far, which count towards the limit in a moves game.
+    pub moves: usize,
+    pub when: DateTime<Utc>,
+}
+
+impl SavedGame {
+    /// Take a snapshot of the game board, the player's hand, their current selection, how their
+    /// score is made up and how far into a game of `mode` they are.
+    pub fn capture(
+        session: &Session,
+        selected: &BlockIndex,
+        scorer: &Scorer,
+        mode: GameMode,
+        moves: usize,
+        assisted: bool,
+        hints: u32,
+    ) -> Self {
+        let game = session.game();
+        let generator = session.generator();
+        SavedGame {
+            rows: game.canvas.rows,
+            columns: game.canvas.columns,
+            occupied: game
+                .canvas
+                .contents()
+                .iter()
+                .map(|p| matches!(p, PointStatus::Occupied))
+                .collect(),
+            obstacles: session.obstacles().to_vec(),
+            score: game.score,
+            blocks: session.hand().to_vec(),
+            selected: selected.current(),
+            assisted,
+            allow_undo: true,
+            hints,
+            seed: Some(generator.seed()),
+            rng_position: Some(generator.position()),
+            streak: scorer.streak(),
+            breakdown: Some(scorer.breakdown().clone()),
+            mode,
+            moves,
+            when: Utc::now(),
+        }
+    }
+
+    /// Note whether undo could be used in the game, it can unless told otherwise.
+    pub fn with_allow_undo(mut self, allow_undo: bool) -> Self {
+        self.allow_undo = allow_undo;
+        self
+    }
+
+    /// Rebuild the game and selection from the snapshot, dealing `hand_size` blocks once the
+    /// saved hand is used up.
+    pub fn restore(&self, hand_size: usize) -> Result<(Session, BlockIndex)> {
+        if self.occupied.len() != self.rows * self.columns {
+            bail!("Saved board does not match its dimensions");
+        }
+        if self.blocks.is_empty() || self.selected >= self.blocks.len() {
+            bail!("Saved hand is invalid");
+        }
+        if !self
+            .obstacles
+            .iter()
+            .all(|&i| self.occupied.get(i) == Some(&true))
+        {
+            bail!("Saved obstacles are not on the board");
+        }
+
+        let game = Game {
+            canvas: build_canvas(self.rows, self.columns, &self.occupied)?,
+            score: self.score,
+        };
+        let selected = BlockIndex::restore(self.selected, self.blocks.len());
+        let session = Session::resume(game, self.blocks.clone(), self.generator(), hand_size)
+            .with_obstacles(self.obstacles.clone());
+
+        Ok((session, selected))
+    }
+
+    /// The scorer, picking up the combo streak where the saved game left off.
+    pub fn scorer(&self) -> Scorer {
+        let breakdown = self
+            .breakdown
+            .clone()
+            .unwrap_or_else(|| Breakdown::lines_only(self.score));
+        Scorer::resume(self.streak, breakdown)
+    }
+
+    /// The block generator, picking up where the saved game left off.
+    ///
+    /// Games saved before seeds were recorded continue with a random seed.
+    pub fn generator(&self) -> BlockGenerator {
+        match (self.seed, self.rng_position) {
+            (Some(seed), Some(position)) => BlockGenerator::resume(seed, position),
+            _ => BlockGenerator::random(),
+        }
+    }
+}
+
+/// Persists a single in-progress game between sessions.
+#[derive(Debug)]
+pub struct LocalSaveSlot {
+    db_conn: Connection,
+}
+
+impl LocalSaveSlot {
+    pub fn new<P>(connection_string: P) -> Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let db_conn = Connection::open(connection_string)?;
+        db_conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS saved_game (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                rows INTEGER NOT NULL,
+                columns INTEGER NOT NULL,
+                canvas TEXT NOT NULL,
+                score INTEGER NOT NULL,
+                blocks TEXT NOT NULL,
+                selected INTEGER NOT NULL,
+                "when" TEXT NOT NULL
+            )"#,
+            (),
+        )?;
+        add_column_if_missing(
+            &db_conn,
+            "saved_game",
+            "assisted",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column_if_missing(&db_conn, "saved_game", "seed", "INTEGER")?;
+        add_column_if_missing(&db_conn, "saved_game", "rng_position", "TEXT")?;
+        add_column_if_missing(
+            &db_conn,
+            "saved_game",
+            "hints",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column_if_missing(
+            &db_conn,
+            "saved_game",
+            "streak",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column_if_missing(&db_conn, "saved_game", "breakdown", "TEXT")?;
+        add_column_if_missing(
+            &db_conn,
+            "saved_game",
+            "mode",
+            "TEXT NOT NULL DEFAULT 'endless'",
+        )?;
+        add_column_if_missing(
+            &db_conn,
+            "saved_game",
+            "moves",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column_if_missing(
+            &db_conn,
+            "saved_game",
+            "obstacles",
+            "TEXT NOT NULL DEFAULT ''",
+        )?;
+        add_column_if_missing(
+            &db_conn,
+            "saved_game",
+            "allow_undo",
+            "INTEGER NOT NULL DEFAULT 1",
+        )?;
+
+        Ok(Self { db_conn })
+    }
+
+    /// Load the saved game, if there is one.
+    pub fn load(&self) -> Result<Option<SavedGame>> {
+        let found = self
+            .db_conn
+            .query_row(
+                r#"
+                SELECT rows, columns, canvas, score, blocks, selected, assisted, "when", seed,
+                    rng_position, hints, streak, breakdown, mode, moves, obstacles, allow_undo
+                FROM saved_game WHERE id = 1
+            "#,
+                (),
+                |row| {
+                    Ok((
+                        row.get::<_, usize>(0)?,
+                        row.get::<_, usize>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, usize>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, usize>(5)?,
+                        row.get::<_, bool>(6)?,
+                        row.get::<_, String>(7)?,
+                        row.get::<_, Option<i64>>(8)?,
+                        row.get::<_, Option<String>>(9)?,
+                        row.get::<_, u32>(10)?,
+                        row.get::<_, usize>(11)?,
+                        row.get::<_, Option<String>>(12)?,
+                        row.get::<_, String>(13)?,
+                        row.get::<_, usize>(14)?,
+                        row.get::<_, String>(15)?,
+                        row.get::<_, bool>(16)?,
+                    ))
+                },
+            )
+            .optional()?;
+
+        let Some((
+            rows,
+            columns,
+            canvas,
+            score,
+            blocks,
+            selected,
+            assisted,
+            when,
+            seed,
+            rng_position,
+            hints,
+            streak,
+            breakdown,
+            mode,
+            moves,
+            obstacles,
+            allow_undo,
+        )) = found
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(SavedGame {
+            rows,
+            columns,
+            occupied: decode_canvas(&canvas)?,
+            obstacles: decode_obstacles(&obstacles)?,
+            score,
+            blocks: decode_blocks(&blocks)?,
+            selected,
+            assisted,
+            allow_undo,
+            hints,
+            // sqlite integers are signed, seeds are stored bit for bit.
+            seed: seed.map(|seed| seed as u64),
+            rng_position: rng_position.map(|p| p.parse()).transpose()?,
+            streak,
+            breakdown: breakdown.map(|b| b.parse()).transpose()?,
+            mode: GameMode::by_name(&mode)
+                .ok_or_else(|| anyhow!("Unknown game mode '{}' in saved game", mode))?,
+            moves,
+            when: DateTime::parse_from_rfc3339(&when)?.with_timezone(&Utc),
+        }))
+    }
+
+    /// Overwrite the saved game.
+    pub fn store(&self, saved: &SavedGame) -> Result<()> {
+        self.db_conn.execute(
+            r#"
+            INSERT OR REPLACE INTO saved_game
+                (id, rows, columns, canvas, score, blocks, selected, assisted, "when", seed,
+                rng_position, hints, streak, breakdown, mode, moves, obstacles, allow_undo)
+            VALUES (1, (?), (?), (?), (?), (?), (?), (?), (?), (?), (?), (?), (?), (?), (?), (?),
+                (?), (?))
+        "#,
+            params![
+                saved.rows,
+                saved.columns,
+                encode_canvas(&saved.occupied),
+                saved.score,
+                encode_blocks(&saved.blocks),
+                saved.selected,
+                saved.assisted,
+                saved.when.to_rfc3339(),
+                saved.seed.map(|seed| seed as i64),
+                saved.rng_position.map(|p| p.to_string()),
+                saved.hints,
+                saved.streak,
+                saved.breakdown.as_ref().map(|b| b.to_string()),
+                saved.mode.name(),
+                saved.moves,
+                encode_obstacles(&saved.obstacles),
+                saved.allow_undo
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Forget the saved game.
+    pub fn clear(&self) -> Result<()> {
+        self.db_conn.execute("DELETE FROM saved_game", ())?;
+        Ok(())
+    }
+}
+
+/// Rebuild a canvas from which of its squares are occupied, row by row.
+pub(crate) fn build_canvas(rows: usize, columns: usize, occupied: &[bool]) -> Result<Canvas> {
+    if occupied.len() != rows * columns {
+        bail!("Board does not match its dimensions");
+    }
+
+    // the canvas keeps its contents private, so fill it one square at a time.
+    let mut canvas = Canvas::new(rows, columns);
+    let square = Block::rectangle(1, 1);
+    for (i, _) in occupied.iter().enumerate().filter(|(_, o)| **o) {
+        let row = (i / columns) as i32;
+        let column = (i % columns) as i32;
+        match canvas.try_make_playable(&square, row, column) {
+            Some(playable) => canvas.add(&playable),
+            None => bail!("Board is invalid"),
+        };
+    }
+
+    Ok(canvas)
+}
+
+// The canvas is stored as one character per square, row by row: `1` for occupied, `0` for empty.
+pub(crate) fn encode_canvas(occupied: &[bool]) -> String {
+    occupied
+        .iter()
+        .map(|o| if *o { '1' } else { '0' })
+        .collect()
+}
+
+pub(crate) fn decode_canvas(encoded: &str) -> Result<Vec<bool>> {
+    encoded
+        .chars()
+        .map(|c| match c {
+            '1' => Ok(true),
+            '0' => Ok(false),
+            _ => bail!("Unexpected character '{}' in saved board", c),
+        })
+        .collect()
+}
+
+// Obstacles are stored as their indexes into the canvas, separated by spaces.
+pub(crate) fn encode_obstacles(obstacles: &[usize]) -> String {
+    obstacles
+        .iter()
+        .map(|i| i.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub(crate) fn decode_obstacles(encoded: &str) -> Result<Vec<usize>> {
+    encoded.split_whitespace().map(|i| Ok(i.parse()?)).collect()
+}
+
+// Blocks are stored as their coordinates, e.g. `0,0 1,0;0,0` is a 2-line followed by a 1x1.
+pub(crate) fn encode_blocks(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .map(|b| {
+            b.coordinates()
+                .iter()
+                .map(|p| format!("{},{}", p.x, p.y))
+                .collect::<Vec<String>>()
+                .join(" ")
+        })
+        .collect::<Vec<String>>()
+        .join(";")
+}
+
+pub(crate) fn decode_blocks(encoded: &str) -> Result<Vec<Block>> {
+    let mut blocks = Vec::new();
+    for block in encoded.split(';') {
+        let mut coordinates = Vec::new();
+        for point in block.split_whitespace() {
+            let Some((x, y)) = point.split_once(',') else {
+                bail!("Unexpected point '{}' in saved hand", point);
+            };
+            coordinates.push(Point {
+                x: x.parse()?,
+                y: y.parse()?,
+            });
+        }
+
+        match shapes::from_coordinates(&coordinates) {
+            Some(b) => blocks.push(b),
+            None => bail!("Unknown block '{}' in saved hand", block),
+        }
+    }
+
+    Ok(blocks)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn save_and_load() {
+        let mut game = Game::default();
+        game.maybe_place_block(&Block::rectangle(3, 2), 1, 2)
+            .unwrap();
+        game.maybe_place_block(&Block::rectangle(1, 1), 7, 7)
+            .unwrap();
+        game.score = 150;
+
+        let mut diagonal = Block::diagonal(3);
+        diagonal.rotate_left();
+        let blocks = vec![Block::tee(), diagonal];
+        let mut selected = BlockIndex::new(blocks.len());
+        selected.next();
+
+        let slot = LocalSaveSlot::new(":memory:").unwrap();
+        assert!(slot.load().unwrap().is_none());
+        let mut generator = BlockGenerator::new(u64::MAX);
+        generator.generate(&game.canvas, 3);
+        let expected = format!("{}", game);
+        let session =
+            Session::resume(game, blocks.clone(), generator.clone(), 3).with_obstacles(vec![63]);
+        let mut scorer = Scorer::resume(0, Breakdown::lines_only(100));
+        scorer.score(1, false);
+        slot.store(&SavedGame::capture(
+            &session,
+            &selected,
+            &scorer,
+            GameMode::Moves,
+            12,
+            true,
+            4,
+        ))
+        .unwrap();
+
+        let saved = slot.load().unwrap().unwrap();
+        assert!(saved.assisted);
+        assert!(saved.allow_undo);
+        assert_eq!(saved.hints, 4);
+        assert_eq!(saved.scorer(), scorer);
+        assert_eq!((saved.mode, saved.moves), (GameMode::Moves, 12));
+        assert_eq!(saved.generator().seed(), u64::MAX);
+        assert_eq!(saved.generator().position(), generator.position());
+        let (restored, restored_selected) = saved.restore(3).unwrap();
+        assert_eq!(restored.score(), 150);
+        assert_eq!(format!("{}", restored.game()), expected);
+        assert_eq!(restored.obstacles(), [63]);
+        assert_eq!(restored_selected.current(), 1);
+        assert_eq!(restored.hand().len(), 2);
+        for (a, b) in restored.hand().iter().zip(blocks.iter()) {
+            assert_eq!(a.coordinates(), b.coordinates());
+        }
+
+        let without_undo = SavedGame::capture(
+            &restored,
+            &restored_selected,
+            &scorer,
+            GameMode::Moves,
+            12,
+            false,
+            4,
+        )
+        .with_allow_undo(false);
+        slot.store(&without_undo).unwrap();
+        assert!(!slot.load().unwrap().unwrap().allow_undo);
+
+        slot.clear().unwrap();
+        assert!(slot.load().unwrap().is_none());
+    }
+
+    #[test]
+    fn unknown_block_is_rejected() {
+        assert!(decode_blocks("0,0 5,5").is_err());
+    }
+}
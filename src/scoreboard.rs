@@ -3,11 +3,22 @@ use chrono::{DateTime, Utc};
 use rusqlite::{Connection, params};
 use std::cmp::Ordering;
 
+use crate::board::Board;
+use crate::db::add_column_if_missing;
+use crate::mode::GameMode;
+use crate::scoring::Breakdown;
+
 #[derive(Debug, Clone)]
 pub struct HighScore {
     pub name: String,
     pub score: i64,
     pub when: DateTime<Utc>,
+    /// The seed the game was played with, if known.
+    pub seed: Option<u64>,
+    /// Number of hints asked for during the game.
+    pub hints: u32,
+    /// Where the points came from, if known.
+    pub breakdown: Option<Breakdown>,
 }
 
 impl HighScore {
@@ -16,8 +27,29 @@ impl HighScore {
             name: name.to_owned(),
             score,
             when,
+            seed: None,
+            hints: 0,
+            breakdown: None,
         }
     }
+
+    /// Note the seed the game was played with.
+    pub fn with_seed(mut self, seed: u64) -> HighScore {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Note how many hints the player asked for.
+    pub fn with_hints(mut self, hints: u32) -> HighScore {
+        self.hints = hints;
+        self
+    }
+
+    /// Note where the points came from.
+    pub fn with_breakdown(mut self, breakdown: Breakdown) -> HighScore {
+        self.breakdown = Some(breakdown);
+        self
+    }
 }
 
 impl PartialEq<HighScore> for HighScore {
@@ -28,15 +60,7 @@ impl PartialEq<HighScore> for HighScore {
 
 impl PartialOrd for HighScore {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        if self.score > other.score {
-            return Some(Ordering::Greater);
-        }
-
-        if self.score < other.score {
-            return Some(Ordering::Less);
-        }
-
-        Some(Ordering::Equal)
+        Some(self.cmp(other))
     }
 }
 
@@ -44,15 +68,7 @@ impl Eq for HighScore {}
 
 impl Ord for HighScore {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self < other {
-            return Ordering::Less;
-        }
-
-        if self > other {
-            return Ordering::Greater;
-        }
-
-        Ordering::Equal
+        self.score.cmp(&other.score)
     }
 
     fn max(self, other: Self) -> Self
@@ -99,7 +115,12 @@ pub trait Scoreboard {
     ///
     /// Returns `Ok(true)` if the score was added to the scoreboard, `Ok(false)` if the score was
     /// not good enough to make the scoreboard.
-    fn add(&mut self, who: &str, score: i64) -> Result<bool>;
+    fn add(&mut self, who: &str, score: i64) -> Result<bool> {
+        self.add_high_score(HighScore::new(who, score, Utc::now()))
+    }
+
+    /// Like `add`, for a score with more details attached.
+    fn add_high_score(&mut self, high_score: HighScore) -> Result<bool>;
 
     /// Get the best top score if one exists.
     fn first(&self) -> Option<HighScore>;
@@ -109,12 +130,24 @@ pub trait Scoreboard {
 
     /// Get all high scores.
     fn all(&self) -> &[HighScore];
+
+    /// The number of high scores kept.
+    fn capacity(&self) -> usize;
+
+    /// Returns true if `score` would make the scoreboard.
+    fn would_rank(&self, score: i64) -> bool {
+        match self.last() {
+            Some(worst) if self.all().len() >= self.capacity() => worst.score <= score,
+            _ => true,
+        }
+    }
 }
 
 /// An in-memory Scoreboard.
 #[derive(Debug)]
 pub struct MinimalScoreboard {
     high_scores: Vec<HighScore>,
+    size: usize,
 }
 
 impl MinimalScoreboard {
@@ -122,6 +155,7 @@ impl MinimalScoreboard {
     pub fn new(n: usize) -> Self {
         MinimalScoreboard {
             high_scores: Vec::with_capacity(n),
+            size: n,
         }
     }
 
@@ -135,19 +169,15 @@ impl MinimalScoreboard {
 }
 
 impl Scoreboard for MinimalScoreboard {
-    fn add(&mut self, who: &str, score: i64) -> Result<bool> {
-        let utc_now = Utc::now();
-
-        if let Some(worst) = self.last() {
-            if worst.score > score {
-                return Ok(false);
-            }
+    fn add_high_score(&mut self, high_score: HighScore) -> Result<bool> {
+        if !self.would_rank(high_score.score) {
+            return Ok(false);
         }
 
-        if self.high_scores.capacity() == self.high_scores.len() {
+        if self.high_scores.len() >= self.size {
             self.high_scores.pop();
         }
-        self.high_scores.push(HighScore::new(who, score, utc_now));
+        self.high_scores.push(high_score);
         self.high_scores.sort_unstable_by(|a, b| b.cmp(a));
         Ok(true)
     }
@@ -171,6 +201,10 @@ impl Scoreboard for MinimalScoreboard {
     fn all(&self) -> &[HighScore] {
         self.high_scores.as_slice()
     }
+
+    fn capacity(&self) -> usize {
+        self.size
+    }
 }
 
 impl Default for MinimalScoreboard {
@@ -179,17 +213,61 @@ impl Default for MinimalScoreboard {
     }
 }
 
+/// A Scoreboard persisted to SQLite.
+///
+/// Scores achieved with the help of undo are "assisted" and ranked on a board of their own. Each
+/// game mode and size of game board has its own pair of scoreboards.
 #[derive(Debug)]
 pub struct LocalScoreBoard {
     internal: MinimalScoreboard,
     db_conn: Connection,
+    assisted: bool,
+    mode: GameMode,
+    board: String,
 }
 
 impl LocalScoreBoard {
+    /// Open the board of unassisted endless scores.
     pub fn new<P>(n: usize, connection_string: P) -> Result<Self>
     where
         P: AsRef<std::path::Path>,
     {
+        Self::for_mode(n, connection_string, GameMode::Endless, false)
+    }
+
+    /// Open the board of endless scores achieved with undo.
+    pub fn new_assisted<P>(n: usize, connection_string: P) -> Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        Self::for_mode(n, connection_string, GameMode::Endless, true)
+    }
+
+    /// Open the board of scores for games played in `mode` on the standard game board.
+    pub fn for_mode<P>(
+        n: usize,
+        connection_string: P,
+        mode: GameMode,
+        assisted: bool,
+    ) -> Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        Self::for_board(n, connection_string, mode, &Board::default(), assisted)
+    }
+
+    /// Open the board of scores for games played in `mode` on `board`.
+    pub fn for_board<P>(
+        n: usize,
+        connection_string: P,
+        mode: GameMode,
+        board: &Board,
+        assisted: bool,
+    ) -> Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let board = board.to_string();
         let db_conn = Connection::open(connection_string)?;
         db_conn.execute(
             r#"
@@ -201,37 +279,76 @@ impl LocalScoreBoard {
             )"#,
             (),
         )?;
+        add_column_if_missing(
+            &db_conn,
+            "scoreboard",
+            "assisted",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column_if_missing(&db_conn, "scoreboard", "seed", "INTEGER")?;
+        add_column_if_missing(
+            &db_conn,
+            "scoreboard",
+            "hints",
+            "INTEGER NOT NULL DEFAULT 0",
+        )?;
+        add_column_if_missing(&db_conn, "scoreboard", "breakdown", "TEXT")?;
+        add_column_if_missing(
+            &db_conn,
+            "scoreboard",
+            "mode",
+            "TEXT NOT NULL DEFAULT 'endless'",
+        )?;
+        add_column_if_missing(
+            &db_conn,
+            "scoreboard",
+            "board",
+            "TEXT NOT NULL DEFAULT '8x8'",
+        )?;
 
         // Ensure the database only contains the top 'n' scores.
         db_conn.execute(
             r#"
             DELETE FROM scoreboard 
-            WHERE rowid NOT IN (
+            WHERE assisted = (?2) AND mode = (?3) AND board = (?4) AND rowid NOT IN (
                 SELECT rowid 
                 FROM scoreboard 
+                WHERE assisted = (?2) AND mode = (?3) AND board = (?4)
                 ORDER BY score DESC 
-                LIMIT (?)
+                LIMIT (?1)
             );
         "#,
-            [n],
+            params![n, assisted, mode.name(), board],
         )?;
 
         let found: Vec<_> = db_conn
-            .prepare("SELECT * FROM scoreboard ORDER BY score DESC LIMIT (?1)")?
-            .query_map([n], |row| {
-                let maybe_date: String = row.get(3)?;
+            .prepare(
+                r#"
+                SELECT name, score, "when", seed, hints, breakdown FROM scoreboard
+                WHERE assisted = (?2) AND mode = (?3) AND board = (?4)
+                ORDER BY score DESC LIMIT (?1)
+            "#,
+            )?
+            .query_map(params![n, assisted, mode.name(), board], |row| {
+                let maybe_date: String = row.get(2)?;
                 Ok(HighScore {
-                    name: row.get(1)?,
-                    score: row.get(2)?,
+                    name: row.get(0)?,
+                    score: row.get(1)?,
                     when: DateTime::parse_from_rfc3339(&maybe_date)
                         .map_err(|_| {
                             rusqlite::Error::InvalidColumnType(
-                                3,
+                                2,
                                 "when".to_string(),
                                 rusqlite::types::Type::Text,
                             )
                         })?
                         .with_timezone(&Utc),
+                    // sqlite integers are signed, seeds are stored bit for bit.
+                    seed: row.get::<_, Option<i64>>(3)?.map(|seed| seed as u64),
+                    hints: row.get(4)?,
+                    breakdown: row
+                        .get::<_, Option<String>>(5)?
+                        .and_then(|b| b.parse().ok()),
                 })
             })?
             .map(|item| item.unwrap())
@@ -239,40 +356,93 @@ impl LocalScoreBoard {
 
         let internal = MinimalScoreboard::init(n, found);
 
-        Ok(Self { internal, db_conn })
+        Ok(Self {
+            internal,
+            db_conn,
+            assisted,
+            mode,
+            board,
+        })
+    }
+
+    /// Every mode and board other than the standard one that has scores, in the order they
+    /// were first played.
+    pub fn other_boards<P>(connection_string: P) -> Result<Vec<(GameMode, Board)>>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        // opening any scoreboard brings the table up to date.
+        let scoreboard = Self::new(1, connection_string)?;
+        let mut statement = scoreboard.db_conn.prepare(
+            r#"
+            SELECT mode, board FROM scoreboard WHERE board != (?1)
+            GROUP BY mode, board ORDER BY MIN(rowid)
+        "#,
+        )?;
+        let found = statement
+            .query_map(params![Board::default().to_string()], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        // anything unrecognised was written by a newer version.
+        Ok(found
+            .into_iter()
+            .filter_map(|(mode, board)| Some((GameMode::by_name(&mode)?, board.parse().ok()?)))
+            .collect())
     }
 }
 
 impl Scoreboard for LocalScoreBoard {
-    fn add(&mut self, who: &str, score: i64) -> Result<bool> {
+    fn add_high_score(&mut self, high_score: HighScore) -> Result<bool> {
         let last = self.internal.last();
-        let added = self.internal.add(who, score);
-        if let Ok(false) = added {
+        let len = self.internal.all().len();
+        if !self.internal.add_high_score(high_score.clone())? {
             return Ok(false);
         }
 
-        if let Some(worst_score) = last {
+        // the worst score only drops off once the board is full.
+        if let Some(worst_score) = last
+            && self.internal.all().len() == len
+        {
             self.db_conn.execute(
                 r#"
                 DELETE FROM scoreboard WHERE
                     name = (?) AND
                     score = (?) AND
-                    "when" = (?)
+                    "when" = (?) AND
+                    assisted = (?) AND
+                    mode = (?) AND
+                    board = (?)
             "#,
                 params![
                     worst_score.name,
                     worst_score.score,
-                    worst_score.when.to_rfc3339()
+                    worst_score.when.to_rfc3339(),
+                    self.assisted,
+                    self.mode.name(),
+                    self.board
                 ],
             )?;
         }
 
         self.db_conn.execute(
             r#"
-            INSERT INTO scoreboard (name, score, "when")
-            VALUES ((?), (?), (?))
+            INSERT INTO scoreboard
+                (name, score, "when", assisted, seed, hints, breakdown, mode, board)
+            VALUES ((?), (?), (?), (?), (?), (?), (?), (?), (?))
         "#,
-            params![who, score, Utc::now().to_rfc3339()],
+            params![
+                high_score.name,
+                high_score.score,
+                high_score.when.to_rfc3339(),
+                self.assisted,
+                high_score.seed.map(|seed| seed as i64),
+                high_score.hints,
+                high_score.breakdown.as_ref().map(|b| b.to_string()),
+                self.mode.name(),
+                self.board
+            ],
         )?;
 
         Ok(true)
@@ -289,6 +459,10 @@ impl Scoreboard for LocalScoreBoard {
     fn all(&self) -> &[HighScore] {
         self.internal.all()
     }
+
+    fn capacity(&self) -> usize {
+        self.internal.capacity()
+    }
 }
 
 #[cfg(test)]
@@ -301,17 +475,17 @@ mod test {
         let a = HighScore::new(
             "Allison",
             100,
-            Utc.with_ymd_and_hms(2001, 01, 01, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2001, 1, 1, 0, 0, 0).unwrap(),
         );
         let b = HighScore::new(
             "Bob",
             90,
-            Utc.with_ymd_and_hms(2002, 02, 02, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2002, 2, 2, 0, 0, 0).unwrap(),
         );
         let c = HighScore::new(
             "Bob",
             90,
-            Utc.with_ymd_and_hms(2003, 01, 02, 0, 0, 0).unwrap(),
+            Utc.with_ymd_and_hms(2003, 1, 2, 0, 0, 0).unwrap(),
         );
 
         assert_ne!(a, b);
@@ -333,7 +507,7 @@ mod test {
                 assert_eq!(high_score.name, "David");
             }
             None => {
-                assert!(false);
+                panic!("Expected a high score.");
             }
         }
 
@@ -345,8 +519,82 @@ mod test {
                 assert_eq!(high_score.name, "Eddie");
             }
             None => {
-                assert!(false);
+                panic!("Expected a high score.");
             }
         }
     }
+
+    #[test]
+    fn assisted_scores_and_modes_are_ranked_separately() {
+        let db_path = std::env::temp_dir().join(format!(
+            "jcblocktui-scoreboard-test-{}.db",
+            std::process::id()
+        ));
+        let big_board = Board {
+            rows: 10,
+            columns: 12,
+            obstacles: 4,
+        };
+
+        {
+            let mut unassisted = LocalScoreBoard::new(3, &db_path).unwrap();
+            let mut assisted = LocalScoreBoard::new_assisted(3, &db_path).unwrap();
+            unassisted.add("Allison", 100).unwrap();
+            LocalScoreBoard::for_mode(3, &db_path, GameMode::Blitz, false)
+                .unwrap()
+                .add("Carol", 900)
+                .unwrap();
+            LocalScoreBoard::for_board(3, &db_path, GameMode::Blitz, &big_board, false)
+                .unwrap()
+                .add("Dave", 2000)
+                .unwrap();
+            assisted
+                .add_high_score(
+                    HighScore::new("Bob", 500, Utc::now())
+                        .with_seed(u64::MAX)
+                        .with_hints(2)
+                        .with_breakdown(Breakdown {
+                            lines: 400,
+                            combo: 100,
+                            best_combo: 2,
+                            ..Breakdown::default()
+                        }),
+                )
+                .unwrap();
+        }
+
+        let unassisted = LocalScoreBoard::new(3, &db_path).unwrap();
+        let assisted = LocalScoreBoard::new_assisted(3, &db_path).unwrap();
+        let blitz = LocalScoreBoard::for_mode(3, &db_path, GameMode::Blitz, false).unwrap();
+        let other_boards = LocalScoreBoard::other_boards(&db_path).unwrap();
+        std::fs::remove_file(&db_path).unwrap();
+
+        assert_eq!(unassisted.all().len(), 1);
+        assert_eq!(unassisted.first().unwrap().name, "Allison");
+        assert_eq!(assisted.all().len(), 1);
+        assert_eq!(assisted.first().unwrap().name, "Bob");
+        assert_eq!(blitz.all().len(), 1);
+        assert_eq!(blitz.first().unwrap().name, "Carol");
+        assert_eq!(other_boards, [(GameMode::Blitz, big_board)]);
+        assert_eq!(assisted.first().unwrap().seed, Some(u64::MAX));
+        assert_eq!(assisted.first().unwrap().hints, 2);
+        assert_eq!(
+            assisted
+                .first()
+                .unwrap()
+                .breakdown
+                .as_ref()
+                .unwrap()
+                .best_combo,
+            2
+        );
+    }
+
+    #[test]
+    fn reloaded_scoreboard_keeps_filling() {
+        let mut sb = MinimalScoreboard::init(3, vec![HighScore::new("Allison", 2, Utc::now())]);
+        sb.add("Bob", 1).unwrap();
+        sb.add("Charlie", 3).unwrap();
+        assert_eq!(sb.all().len(), 3);
+    }
 }
@@ -0,0 +1,199 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{Result, anyhow, bail};
+use jcblocks::canvas::{Canvas, PointStatus};
+
+use crate::session::POINTS_PER_LINE_CLEAR;
+
+/// Extra points for each pair of lines cleared by the same placement.
+const MULTI_LINE_BONUS: usize = 25;
+/// Extra points for leaving the board empty.
+const BOARD_CLEAR_BONUS: usize = 300;
+
+/// Where a game's points came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Breakdown {
+    /// Points for the lines themselves, the same as `jcblocks` gives.
+    pub lines: usize,
+    /// Bonus for clearing several lines at once.
+    pub multi_line: usize,
+    /// Bonus for clearing lines on consecutive placements.
+    pub combo: usize,
+    /// Bonus for emptying the board.
+    pub board_clears: usize,
+    /// The longest run of consecutive placements that cleared lines.
+    pub best_combo: usize,
+}
+
+impl Breakdown {
+    /// The breakdown of a game scored before combos existed, when every point came from lines.
+    pub fn lines_only(score: usize) -> Self {
+        Breakdown {
+            lines: score,
+            ..Breakdown::default()
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.lines + self.multi_line + self.combo + self.board_clears
+    }
+}
+
+// Stored as `key=value` pairs separated by spaces, e.g. `lines=150 multi_line=25 ...`.
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "lines={} multi_line={} combo={} board_clears={} best_combo={}",
+            self.lines, self.multi_line, self.combo, self.board_clears, self.best_combo
+        )
+    }
+}
+
+impl FromStr for Breakdown {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut breakdown = Breakdown::default();
+        for pair in s.split_whitespace() {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected key=value in score breakdown, got '{}'", pair))?;
+            let field = match key {
+                "lines" => &mut breakdown.lines,
+                "multi_line" => &mut breakdown.multi_line,
+                "combo" => &mut breakdown.combo,
+                "board_clears" => &mut breakdown.board_clears,
+                "best_combo" => &mut breakdown.best_combo,
+                _ => bail!("Unknown key '{}' in score breakdown", key),
+            };
+            *field = value.parse()?;
+        }
+
+        Ok(breakdown)
+    }
+}
+
+/// The points one placement earned, for showing to the player.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Award {
+    pub points: usize,
+    pub lines: usize,
+    /// How many placements in a row have cleared lines, including this one.
+    pub combo: usize,
+    pub board_clear: bool,
+}
+
+impl fmt::Display for Award {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{}", self.points)?;
+        if self.lines > 1 {
+            write!(f, " {} LINES", self.lines)?;
+        }
+        if self.combo > 1 {
+            write!(f, " COMBO x{}", self.combo)?;
+        }
+        if self.board_clear {
+            write!(f, " CLEAR!")?;
+        }
+        Ok(())
+    }
+}
+
+/// Scores placements on top of the points `jcblocks` gives for lines.
+///
+/// Clearing several lines at once earns a bonus, and clearing lines on consecutive placements
+/// multiplies the points by the length of the streak. A placement that clears nothing ends the
+/// streak. Emptying the board earns a flat bonus.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scorer {
+    streak: usize,
+    breakdown: Breakdown,
+}
+
+impl Scorer {
+    /// Carry on scoring a game part way through.
+    pub fn resume(streak: usize, breakdown: Breakdown) -> Self {
+        Scorer { streak, breakdown }
+    }
+
+    /// How many placements in a row have cleared lines.
+    pub fn streak(&self) -> usize {
+        self.streak
+    }
+
+    pub fn breakdown(&self) -> &Breakdown {
+        &self.breakdown
+    }
+
+    pub fn total(&self) -> usize {
+        self.breakdown.total()
+    }
+
+    /// Score a placement that cleared `lines_cleared` lines, returning what it earned if anything.
+    pub fn score(&mut self, lines_cleared: usize, board_empty: bool) -> Option<Award> {
+        if lines_cleared == 0 {
+            self.streak = 0;
+            return None;
+        }
+
+        self.streak += 1;
+        let lines = lines_cleared * POINTS_PER_LINE_CLEAR;
+        let multi_line = MULTI_LINE_BONUS * lines_cleared * (lines_cleared - 1);
+        let combo = (lines + multi_line) * (self.streak - 1);
+        let board_clear = if board_empty { BOARD_CLEAR_BONUS } else { 0 };
+
+        self.breakdown.lines += lines;
+        self.breakdown.multi_line += multi_line;
+        self.breakdown.combo += combo;
+        self.breakdown.board_clears += board_clear;
+        self.breakdown.best_combo = self.breakdown.best_combo.max(self.streak);
+
+        Some(Award {
+            points: lines + multi_line + combo + board_clear,
+            lines: lines_cleared,
+            combo: self.streak,
+            board_clear: board_empty,
+        })
+    }
+}
+
+/// Nothing but `obstacles` is left on the board.
+pub fn is_empty(canvas: &Canvas, obstacles: &[usize]) -> bool {
+    !canvas
+        .contents()
+        .iter()
+        .enumerate()
+        .any(|(i, p)| matches!(p, PointStatus::Occupied) && !obstacles.contains(&i))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn streaks_multiply_and_reset() {
+        let mut scorer = Scorer::default();
+        assert_eq!(scorer.score(1, false).unwrap().to_string(), "+50");
+        assert_eq!(
+            scorer.score(2, false).unwrap().to_string(),
+            "+300 2 LINES COMBO x2"
+        );
+        assert_eq!(scorer.score(0, false), None);
+        assert_eq!(scorer.streak(), 0);
+        assert_eq!(scorer.score(1, true).unwrap().to_string(), "+350 CLEAR!");
+
+        let breakdown = scorer.breakdown();
+        assert_eq!(breakdown.lines, 200);
+        assert_eq!(breakdown.multi_line, 50);
+        assert_eq!(breakdown.combo, 150);
+        assert_eq!(breakdown.board_clears, 300);
+        assert_eq!(breakdown.best_combo, 2);
+        assert_eq!(scorer.total(), 700);
+        assert_eq!(
+            breakdown.to_string().parse::<Breakdown>().unwrap(),
+            *breakdown
+        );
+        assert!("lines=1 bonus=2".parse::<Breakdown>().is_err());
+    }
+}
@@ -0,0 +1,454 @@
+use anyhow::{Result, bail};
+use jcblocks::{
+    block::{Block, Point},
+    canvas::{Canvas, PointStatus},
+    game::Game,
+};
+
+use crate::board::{self, Board};
+use crate::generator::BlockGenerator;
+use crate::scoring;
+
+/// Points for each row or column cleared, as awarded by `jcblocks`.
+pub const POINTS_PER_LINE_CLEAR: usize = 50;
+
+/// Placing the block at `selected` in the hand with its origin at `row` and `column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub selected: usize,
+    pub row: i32,
+    pub column: i32,
+}
+
+/// What a move did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placed {
+    pub points: usize,
+    pub lines_cleared: usize,
+    /// The squares emptied by clearing lines, as indexes into the canvas contents.
+    pub cleared: Vec<usize>,
+    /// The hand was used up and a new one dealt.
+    pub dealt: bool,
+}
+
+/// One game, without any input handling or drawing.
+///
+/// This holds the rules: blocks are dealt from the generator, a new hand is dealt once the last
+/// block is placed and the game is over when nothing in the hand fits.
+#[derive(Debug)]
+pub struct Session {
+    game: Game,
+    hand: Vec<Block>,
+    generator: BlockGenerator,
+    blocks_per_turn: usize,
+    game_over: bool,
+    /// Squares that can't be cleared, as indexes into the canvas.
+    obstacles: Vec<usize>,
+}
+
+impl Session {
+    /// Start a game on an empty board.
+    pub fn new(generator: BlockGenerator, blocks_per_turn: usize) -> Self {
+        Session::start(Game::default(), generator, blocks_per_turn)
+    }
+
+    /// Start a game on an empty board of the given size, with its obstacles scattered from the
+    /// generator's seed.
+    pub fn on_board(board: &Board, generator: BlockGenerator, blocks_per_turn: usize) -> Self {
+        let obstacles = board.scatter(generator.seed());
+        let game = Game {
+            canvas: board.canvas(&obstacles),
+            score: 0,
+        };
+
+        Session::start(game, generator, blocks_per_turn).with_obstacles(obstacles)
+    }
+
+    /// Start a game on the given board, dealing the first hand.
+    pub fn start(game: Game, mut generator: BlockGenerator, blocks_per_turn: usize) -> Self {
+        let hand = generator
+            .generate(&game.canvas, blocks_per_turn)
+            .expect("Should be able to generate blocks for an empty canvas.");
+
+        Session::resume(game, hand, generator, blocks_per_turn)
+    }
+
+    /// Carry on a game part way through a hand.
+    pub fn resume(
+        game: Game,
+        hand: Vec<Block>,
+        generator: BlockGenerator,
+        blocks_per_turn: usize,
+    ) -> Self {
+        let mut session = Session {
+            game,
+            hand,
+            generator,
+            blocks_per_turn,
+            game_over: false,
+            obstacles: Vec::new(),
+        };
+        session.game_over = !session.can_move();
+        session
+    }
+
+    /// Keep the squares at `obstacles`, which must already be occupied, from ever being cleared.
+    pub fn with_obstacles(mut self, obstacles: Vec<usize>) -> Self {
+        self.obstacles = obstacles;
+        self
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn canvas(&self) -> &Canvas {
+        &self.game.canvas
+    }
+
+    pub fn score(&self) -> usize {
+        self.game.score
+    }
+
+    /// The blocks left to place this turn.
+    pub fn hand(&self) -> &[Block] {
+        &self.hand
+    }
+
+    pub fn generator(&self) -> &BlockGenerator {
+        &self.generator
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.generator.seed()
+    }
+
+    pub fn blocks_per_turn(&self) -> usize {
+        self.blocks_per_turn
+    }
+
+    pub fn obstacles(&self) -> &[usize] {
+        &self.obstacles
+    }
+
+    /// Nothing but obstacles is left on the board.
+    pub fn is_clear(&self) -> bool {
+        scoring::is_empty(&self.game.canvas, &self.obstacles)
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// End the game early.
+    pub fn resign(&mut self) {
+        self.game_over = true;
+    }
+
+    /// Swap a hand that doesn't fit anywhere for a freshly dealt one that does, returning false
+    /// if the board is too full for any hand at all.
+    pub fn reshuffle(&mut self) -> bool {
+        match self
+            .generator
+            .generate(&self.game.canvas, self.blocks_per_turn)
+        {
+            Some(blocks) => {
+                self.hand = blocks;
+                self.game_over = !self.can_move();
+            }
+            None => self.game_over = true,
+        }
+
+        !self.game_over
+    }
+
+    pub fn is_legal(&self, m: &Move) -> bool {
+        !self.game_over
+            && self
+                .hand
+                .get(m.selected)
+                .is_some_and(|block| self.game.canvas.can_fit_at(block, m.row, m.column))
+    }
+
+    /// Whether the block at `selected` in the hand fits anywhere on the board.
+    pub fn is_playable(&self, selected: usize) -> bool {
+        selected < self.hand.len() && !self.placements(selected).is_empty()
+    }
+
+    /// Every move that can be made right now.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.game_over {
+            return Vec::new();
+        }
+
+        (0..self.hand.len())
+            .flat_map(|selected| self.placements(selected))
+            .collect()
+    }
+
+    /// Everywhere the block at `selected` fits.
+    fn placements(&self, selected: usize) -> Vec<Move> {
+        positions(&self.game.canvas, &self.hand[selected])
+            .into_iter()
+            .map(|(row, column)| Move {
+                selected,
+                row,
+                column,
+            })
+            .collect()
+    }
+
+    /// Place a block, dealing a new hand if it was the last one and checking whether the game
+    /// can go on.
+    pub fn apply(&mut self, m: &Move) -> Result<Placed> {
+        if self.game_over {
+            bail!("The game is over");
+        }
+        let Some(block) = self.hand.get(m.selected) else {
+            bail!("There is no block {} in the hand", m.selected);
+        };
+
+        let before = self.game.score;
+        let mut was_occupied = occupied(&self.game.canvas);
+        let columns = self.game.canvas.columns as i32;
+        for p in block.coordinates() {
+            if let Some(square) =
+                was_occupied.get_mut(((p.y + m.row) * columns + p.x + m.column) as usize)
+            {
+                *square = true;
+            }
+        }
+        if self.game.maybe_place_block(block, m.row, m.column).is_err() {
+            bail!("The block doesn't fit there");
+        }
+        board::restore_obstacles(&mut self.game.canvas, &self.obstacles);
+        self.hand.remove(m.selected);
+
+        let dealt = self.hand.is_empty();
+        if dealt {
+            // only a puzzle's fixed sequence runs out, which leaves nothing to play.
+            if let Some(blocks) = self
+                .generator
+                .generate(&self.game.canvas, self.blocks_per_turn)
+            {
+                self.hand = blocks;
+            }
+        }
+        self.game_over = !self.can_move();
+
+        // anything occupied before or by the block, and empty now, was in a cleared line. Obstacles
+        // are back in place, so they aren't included.
+        let cleared = occupied(&self.game.canvas)
+            .into_iter()
+            .zip(was_occupied)
+            .enumerate()
+            .filter(|(_, (now, was))| *was && !*now)
+            .map(|(i, _)| i)
+            .collect();
+
+        let points = self.game.score - before;
+        Ok(Placed {
+            points,
+            lines_cleared: points / POINTS_PER_LINE_CLEAR,
+            cleared,
+            dealt,
+        })
+    }
+
+    /// Let `player` make every move until the game is over, returning the final score.
+    ///
+    /// A player that gives up ends the game where it stands.
+    pub fn play(&mut self, player: &mut impl Player) -> Result<usize> {
+        while !self.game_over {
+            match player.choose(self) {
+                Some(m) => {
+                    self.apply(&m)?;
+                }
+                None => self.resign(),
+            }
+        }
+
+        Ok(self.game.score)
+    }
+
+    fn can_move(&self) -> bool {
+        (0..self.hand.len()).any(|selected| self.is_playable(selected))
+    }
+}
+
+/// Every row and column the origin of `block` can go to on `canvas`.
+pub fn positions(canvas: &Canvas, block: &Block) -> Vec<(i32, i32)> {
+    // block coordinates can be negative, so the origin may be off the board.
+    let (min, max) = extent(block);
+
+    let mut positions = Vec::new();
+    for row in -min.y..canvas.rows as i32 - max.y {
+        for column in -min.x..canvas.columns as i32 - max.x {
+            if canvas.can_fit_at(block, row, column) {
+                positions.push((row, column));
+            }
+        }
+    }
+
+    positions
+}
+
+/// Which squares of `canvas` are occupied, row by row.
+fn occupied(canvas: &Canvas) -> Vec<bool> {
+    canvas
+        .contents()
+        .iter()
+        .map(|p| matches!(p, PointStatus::Occupied))
+        .collect()
+}
+
+/// The smallest and largest coordinates of `block`, relative to its origin.
+pub fn extent(block: &Block) -> (Point, Point) {
+    let xs = block.coordinates().iter().map(|p| p.x);
+    let ys = block.coordinates().iter().map(|p| p.y);
+    (
+        Point {
+            x: xs.clone().min().unwrap_or(0),
+            y: ys.clone().min().unwrap_or(0),
+        },
+        Point {
+            x: xs.max().unwrap_or(0),
+            y: ys.max().unwrap_or(0),
+        },
+    )
+}
+
+impl Clone for Session {
+    fn clone(&self) -> Self {
+        Session {
+            game: Game {
+                canvas: self.game.canvas.clone(),
+                score: self.game.score,
+            },
+            hand: self.hand.clone(),
+            generator: self.generator.clone(),
+            blocks_per_turn: self.blocks_per_turn,
+            game_over: self.game_over,
+            obstacles: self.obstacles.clone(),
+        }
+    }
+}
+
+/// Something that can play the game, such as a bot.
+pub trait Player {
+    /// Pick the next move, or `None` to give up.
+    fn choose(&mut self, session: &Session) -> Option<Move>;
+}
+
+impl<F> Player for F
+where
+    F: FnMut(&Session) -> Option<Move>,
+{
+    fn choose(&mut self, session: &Session) -> Option<Move> {
+        self(session)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn legal_moves_are_legal() {
+        let mut session = Session::new(BlockGenerator::new(3), 3);
+        let moves = session.legal_moves();
+        assert!(!moves.is_empty());
+        for m in moves.iter() {
+            assert!(session.clone().apply(m).is_ok());
+        }
+
+        let illegal = Move {
+            selected: 0,
+            row: -5,
+            column: 0,
+        };
+        assert!(!session.is_legal(&illegal));
+        assert!(session.apply(&illegal).is_err());
+        assert_eq!(session.hand().len(), 3);
+    }
+
+    #[test]
+    fn game_goes_on_while_any_block_fits() {
+        // a checkerboard has room for single squares and nothing bigger.
+        let checkerboard: Vec<bool> = (0..64).map(|i| (i / 8 + i % 8) % 2 == 0).collect();
+        let game = || Game {
+            canvas: crate::save::build_canvas(8, 8, &checkerboard).unwrap(),
+            score: 0,
+        };
+
+        let hand = vec![Block::rectangle(2, 2), Block::rectangle(1, 1)];
+        let session = Session::resume(game(), hand, BlockGenerator::new(1), 3);
+        assert!(!session.is_playable(0));
+        assert!(session.is_playable(1));
+        assert!(!session.is_game_over());
+        assert!(session.legal_moves().iter().all(|m| m.selected == 1));
+
+        let hand = vec![Block::rectangle(2, 2), Block::line(2)];
+        let mut session = Session::resume(game(), hand, BlockGenerator::new(1), 3);
+        assert!(session.is_game_over());
+
+        // only single squares are dealt in place of the dead hand.
+        assert!(session.reshuffle());
+        assert_eq!(session.hand().len(), 3);
+        assert!((0..3).all(|selected| session.is_playable(selected)));
+    }
+
+    #[test]
+    fn cleared_lines_are_reported() {
+        let mut game = Game::default();
+        game.maybe_place_block(&Block::line(5), 0, 0).unwrap();
+        game.maybe_place_block(&Block::line(2), 0, 5).unwrap();
+        let hand = vec![Block::rectangle(1, 1), Block::tee()];
+        let mut session = Session::resume(game, hand, BlockGenerator::new(1), 3);
+
+        let placed = session
+            .apply(&Move {
+                selected: 0,
+                row: 0,
+                column: 7,
+            })
+            .unwrap();
+        assert_eq!(placed.lines_cleared, 1);
+        assert_eq!(placed.cleared, (0..8).collect::<Vec<_>>());
+        assert!(session.is_clear());
+
+        // an obstacle helps complete the line, and is still there once it's cleared.
+        let mut game = Game::default();
+        game.maybe_place_block(&Block::line(5), 0, 0).unwrap();
+        game.maybe_place_block(&Block::line(2), 0, 5).unwrap();
+        let hand = vec![Block::rectangle(1, 1), Block::tee()];
+        let mut session =
+            Session::resume(game, hand, BlockGenerator::new(1), 3).with_obstacles(vec![6]);
+        let placed = session
+            .apply(&Move {
+                selected: 0,
+                row: 0,
+                column: 7,
+            })
+            .unwrap();
+        assert_eq!(placed.lines_cleared, 1);
+        assert!(!placed.cleared.contains(&6));
+        assert!(matches!(
+            session.canvas().contents()[6],
+            PointStatus::Occupied
+        ));
+        assert!(session.is_clear());
+    }
+
+    #[test]
+    fn first_fit_plays_to_the_end() {
+        let mut first_fit = |session: &Session| session.legal_moves().first().copied();
+
+        let mut a = Session::new(BlockGenerator::new(11), 3);
+        let mut b = Session::new(BlockGenerator::new(11), 3);
+        let score = a.play(&mut first_fit).unwrap();
+        assert!(a.is_game_over());
+        assert!(a.legal_moves().is_empty());
+        assert_eq!(b.play(&mut first_fit).unwrap(), score);
+    }
+}
@@ -0,0 +1,78 @@
+use jcblocks::block::{Block, Point};
+
+/// Every shape that can be dealt to the player, in its unrotated orientation.
+///
+/// This mirrors the set of shapes used by `jcblocks` when generating a hand.
+pub fn catalogue() -> Vec<Block> {
+    vec![
+        Block::rectangle(3, 3),
+        Block::rectangle(3, 2),
+        Block::rectangle(2, 3),
+        Block::rectangle(2, 2),
+        Block::rectangle(1, 1),
+        Block::tee(),
+        Block::line(2),
+        Block::line(3),
+        Block::line(4),
+        Block::line(5),
+        Block::elle(3, 3),
+        Block::elle(3, 2),
+        Block::elle(2, 3),
+        Block::elle(2, 2),
+        Block::diagonal(2),
+        Block::diagonal(3),
+        Block::diagonal(4),
+    ]
+}
+
+/// Rebuild a block from its coordinates.
+///
+/// Blocks only expose their coordinates, so the shape is recovered by matching against every
+/// rotation of every shape in the catalogue. Returns `None` if nothing matches.
+pub fn from_coordinates(coordinates: &[Point]) -> Option<Block> {
+    let wanted = sorted(coordinates);
+    for mut block in catalogue() {
+        for _ in (0..360).step_by(90) {
+            if sorted(block.coordinates()) == wanted {
+                return Some(block);
+            }
+            block.rotate_left();
+        }
+    }
+
+    None
+}
+
+/// Like `from_coordinates`, but the shape can be anywhere: only the squares' positions relative
+/// to each other have to match.
+pub fn from_shape(coordinates: &[Point]) -> Option<Block> {
+    let wanted = normalized(coordinates);
+    for mut block in catalogue() {
+        for _ in (0..360).step_by(90) {
+            if normalized(block.coordinates()) == wanted {
+                return Some(block);
+            }
+            block.rotate_left();
+        }
+    }
+
+    None
+}
+
+/// The coordinates moved so the smallest x and y are both 0, then sorted.
+fn normalized(coordinates: &[Point]) -> Vec<(i32, i32)> {
+    let min_x = coordinates.iter().map(|p| p.x).min().unwrap_or(0);
+    let min_y = coordinates.iter().map(|p| p.y).min().unwrap_or(0);
+    let mut pairs: Vec<(i32, i32)> = coordinates
+        .iter()
+        .map(|p| (p.x - min_x, p.y - min_y))
+        .collect();
+    pairs.sort_unstable();
+    pairs
+}
+
+fn sorted(coordinates: &[Point]) -> Vec<(i32, i32)> {
+    let mut pairs: Vec<(i32, i32)> = coordinates.iter().map(|p| (p.x, p.y)).collect();
+    pairs.sort_unstable();
+    pairs
+}
@@ -0,0 +1,101 @@
+use ratatui::style::{Color, Modifier, Style, Stylize};
+
+/// The colors used to draw the game.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub occupied: Style,
+    pub empty: Style,
+    pub obstacle: Style,
+    pub hovered: Style,
+    pub conflict: Style,
+    pub blast: Style,
+    pub hint: Style,
+    pub game_over: Style,
+    pub selected_block: Style,
+    pub unselected_block: Style,
+    /// Blocks in the hand that don't fit anywhere on the board.
+    pub unplayable: Style,
+    pub scoreboard: Style,
+    pub key_hint: Style,
+    pub warning: Style,
+}
+
+impl Theme {
+    /// Names accepted by `Theme::by_name`.
+    pub const NAMES: [&'static str; 3] = ["classic", "high-contrast", "mono"];
+
+    /// Look up one of the built-in themes.
+    pub fn by_name(name: &str) -> Option<Theme> {
+        match name {
+            "classic" => Some(Theme::classic()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "mono" => Some(Theme::mono()),
+            _ => None,
+        }
+    }
+
+    pub fn classic() -> Theme {
+        Theme {
+            occupied: Style::new().blue(),
+            empty: Style::new().dark_gray(),
+            obstacle: Style::new().gray(),
+            hovered: Style::new().magenta(),
+            conflict: Style::new().red(),
+            blast: Style::new().yellow(),
+            hint: Style::new().green(),
+            game_over: Style::new().gray(),
+            selected_block: Style::new().magenta(),
+            unselected_block: Style::new().black(),
+            unplayable: Style::new().dark_gray(),
+            scoreboard: Style::new().yellow(),
+            key_hint: Style::new().blue().bold(),
+            warning: Style::new().red(),
+        }
+    }
+
+    pub fn high_contrast() -> Theme {
+        Theme {
+            occupied: Style::new().white().bold(),
+            empty: Style::new().gray(),
+            obstacle: Style::new().light_magenta(),
+            hovered: Style::new().light_cyan().bold(),
+            conflict: Style::new().light_red().bold(),
+            blast: Style::new().light_yellow().bold(),
+            hint: Style::new().light_green().bold(),
+            game_over: Style::new().dark_gray(),
+            selected_block: Style::new().light_cyan().bold(),
+            unselected_block: Style::new().white(),
+            unplayable: Style::new().dark_gray(),
+            scoreboard: Style::new().light_yellow(),
+            key_hint: Style::new().light_cyan().bold(),
+            warning: Style::new().light_red().bold(),
+        }
+    }
+
+    /// No colors at all, for terminals without color support or players who prefer `NO_COLOR`.
+    pub fn mono() -> Theme {
+        let plain = Style::new().fg(Color::Reset);
+        Theme {
+            occupied: plain,
+            empty: plain.add_modifier(Modifier::DIM),
+            obstacle: plain.add_modifier(Modifier::BOLD),
+            hovered: plain.add_modifier(Modifier::REVERSED),
+            conflict: plain.add_modifier(Modifier::CROSSED_OUT | Modifier::REVERSED),
+            blast: plain.add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            hint: plain.add_modifier(Modifier::UNDERLINED),
+            game_over: plain.add_modifier(Modifier::DIM),
+            selected_block: plain.add_modifier(Modifier::BOLD),
+            unselected_block: plain.add_modifier(Modifier::DIM),
+            unplayable: plain.add_modifier(Modifier::DIM | Modifier::CROSSED_OUT),
+            scoreboard: plain,
+            key_hint: plain.add_modifier(Modifier::BOLD),
+            warning: plain.add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::classic()
+    }
+}